@@ -0,0 +1,35 @@
+use lite_graphics::{
+    color::{DirectionalGradient, Interpolation, Rgba},
+    text::BitmapFont,
+    Buffer, Drawable, Offset,
+};
+
+fn main() {
+    let path = std::env::args()
+        .nth(1)
+        .expect("usage: bitmap_font <FONT.bdf|FONT.psf>");
+    let data = std::fs::read(&path).unwrap();
+    let font = if path.ends_with(".bdf") {
+        BitmapFont::from_bdf(&data)
+    } else {
+        BitmapFont::from_psf(&data)
+    }
+    .unwrap();
+
+    let buf = Buffer::new(400, 300);
+    let gradient = DirectionalGradient::new(
+        &[(0.0, Rgba::RED), (1.0, Rgba::BLUE)],
+        false,
+        Interpolation::Srgb,
+        0.,
+        300.,
+        Offset { x: 50, y: 0 },
+    );
+    buf.text_font(
+        Offset::new(50, 100),
+        "The quick brown fox\njumps over the lazy dog",
+        &font,
+        gradient.into(),
+    );
+    buf.draw();
+}
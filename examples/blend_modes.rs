@@ -0,0 +1,50 @@
+use lite_graphics::{
+    blend::BlendMode,
+    color::{Color, DirectionalGradient, Interpolation, Rgba},
+    Buffer, Drawable, Offset, Rect, Size,
+};
+
+fn main() {
+    let mut buf = Buffer::new(400, 300);
+    buf.fill_rect(
+        Size::new(400, 300).into(),
+        DirectionalGradient::new(
+            &[(0.0, Rgba::YELLOW), (0.5, Rgba::CYAN), (1.0, Rgba::MAGENTA)],
+            true,
+            Interpolation::Srgb,
+            0.5,
+            200.,
+            Offset::new(0, 0),
+        )
+        .into(),
+    );
+    let modes = [
+        BlendMode::Normal,
+        BlendMode::Multiply,
+        BlendMode::Screen,
+        BlendMode::Overlay,
+        BlendMode::Darken,
+        BlendMode::Lighten,
+        BlendMode::ColorDodge,
+        BlendMode::ColorBurn,
+        BlendMode::HardLight,
+        BlendMode::SoftLight,
+        BlendMode::Difference,
+        BlendMode::Exclusion,
+        BlendMode::Hue,
+        BlendMode::Saturation,
+        BlendMode::Color,
+        BlendMode::Luminosity,
+    ];
+    for (i, mode) in modes.into_iter().enumerate() {
+        let cell = Offset::new(100 * (i as i32 % 4), 75 * (i as i32 / 4));
+        buf.set_blend_mode(mode);
+        buf.fill_rect(
+            Rect::new(cell + Offset::new(15, 10), Size::new(30, 55)),
+            Color::DARK_BLUE,
+        );
+        buf.fill_circle_aa(cell + Offset::new(60, 37), 25, Rgba::RED.set_a(200).into());
+    }
+    buf.set_blend_mode(BlendMode::Normal);
+    buf.draw();
+}
@@ -0,0 +1,31 @@
+use lite_graphics::{color::Rgba, Buffer, Drawable, Offset, Rect, Size};
+
+fn main() {
+    let buf = Buffer::new(400, 300);
+
+    // Button states derived from one base color.
+    let base = Rgba::from_hsl(210., 0.7, 0.5);
+    let states = [
+        base,
+        base.lighten(0.1),
+        base.darken(0.1),
+        base.saturate(-0.6),
+    ];
+    for (i, color) in states.into_iter().enumerate() {
+        let rect = Rect::new(Offset::new(20 + 95 * i as i32, 20), Size::new(80, 40));
+        buf.fill_round_rect_aa(rect, 8, color.into());
+    }
+
+    // Hue wheels in HSV and OKLCh, OKLCh keeping a steady lightness.
+    for i in 0..36 {
+        let hue = i as f32 * 10.;
+        let x = 20 + 10 * i;
+        let hsv = Rect::new(Offset::new(x, 100), Size::new(10, 60));
+        buf.fill_rect(hsv, Rgba::from_hsv(hue, 1., 1.).into());
+        let oklch = Rect::new(Offset::new(x, 170), Size::new(10, 60));
+        buf.fill_rect(oklch, Rgba::from_oklch(0.7, 0.12, hue).into());
+        let rotated = Rect::new(Offset::new(x, 240), Size::new(10, 40));
+        buf.fill_rect(rotated, base.rotate_hue(hue).into());
+    }
+    buf.draw();
+}
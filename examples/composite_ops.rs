@@ -0,0 +1,40 @@
+use lite_graphics::{
+    blend::CompositeOp,
+    color::{Color, Rgba},
+    Buffer, Drawable, Offset, Overlay, Rect, Size,
+};
+
+fn main() {
+    let buf = Buffer::new(400, 300);
+    let ops = [
+        CompositeOp::Clear,
+        CompositeOp::Src,
+        CompositeOp::Dst,
+        CompositeOp::SrcOver,
+        CompositeOp::DstOver,
+        CompositeOp::SrcIn,
+        CompositeOp::DstIn,
+        CompositeOp::SrcOut,
+        CompositeOp::DstOut,
+        CompositeOp::SrcAtop,
+        CompositeOp::DstAtop,
+        CompositeOp::Xor,
+        CompositeOp::Plus,
+    ];
+    for (i, op) in ops.into_iter().enumerate() {
+        let cell = Offset::new(100 * (i as i32 % 4), 75 * (i as i32 / 4));
+        buf.fill_rect(
+            Rect::new(cell + Offset::new(10, 10), Size::new(50, 40)),
+            Color::BLUE,
+        );
+
+        let overlay = Overlay::new(
+            buf.clone(),
+            Rect::new(cell + Offset::new(5, 5), Size::new(90, 65)),
+        );
+        overlay.fill_circle_aa(Offset::new(55, 40), 22, Rgba::RED.set_a(200).into());
+        // Fade the second half of the operators in at half opacity.
+        overlay.write_with(op, if i < 7 { 255 } else { 128 });
+    }
+    buf.draw();
+}
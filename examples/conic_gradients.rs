@@ -0,0 +1,38 @@
+use std::f32::consts::PI;
+
+use lite_graphics::{
+    color::{Color, ConicGradient, Interpolation, Rgba},
+    Buffer, Drawable, Offset,
+};
+
+fn main() {
+    let buf = Buffer::new(400, 300);
+    let wheel = [
+        (0.0, Rgba::RED),
+        (1. / 6., Rgba::YELLOW),
+        (2. / 6., Rgba::GREEN),
+        (3. / 6., Rgba::CYAN),
+        (4. / 6., Rgba::BLUE),
+        (5. / 6., Rgba::MAGENTA),
+        (1.0, Rgba::RED),
+    ];
+    let center = Offset::new(100, 150);
+    buf.fill_circle_aa(
+        center,
+        80,
+        ConicGradient::new(&wheel, Interpolation::Srgb, center, 0.).into(),
+    );
+
+    // A gauge sweeping counterclockwise from the bottom right to the bottom left, shaded along its sweep.
+    let center = Offset::new(290, 150);
+    let (start, end) = (PI * 1.75, PI * 1.25);
+    let gauge = ConicGradient::new(
+        &[(0.0, Rgba::RED), (0.75, Rgba::GREEN)],
+        Interpolation::Oklab,
+        center,
+        start,
+    );
+    buf.circle_pie_aa(center, 80, start, end, gauge.into());
+    buf.fill_circle_aa(center, 60, Color::WHITE);
+    buf.draw();
+}
@@ -0,0 +1,64 @@
+use lite_graphics::{color::Color, Buffer, Drawable, LineCap, Offset, Rect, StrokeStyle};
+
+fn main() {
+    let buf = Buffer::new(400, 300);
+    let selection = StrokeStyle {
+        dash: vec![6., 3.],
+        // Increase over time for marching ants.
+        dash_offset: 2.,
+        ..Default::default()
+    };
+    buf.rect_styled(
+        Rect {
+            x: 20,
+            y: 20,
+            w: 100,
+            h: 60,
+        },
+        &selection,
+        Color::BLACK,
+    );
+    let dotted = StrokeStyle {
+        width: 3.,
+        cap: LineCap::Round,
+        dash: vec![0., 6.],
+        ..Default::default()
+    };
+    buf.line_styled(
+        Offset::new(20, 120),
+        Offset::new(200, 140),
+        &dotted,
+        Color::BLUE,
+    );
+    let dash_dot = StrokeStyle {
+        width: 3.,
+        dash: vec![12., 5., 3., 5.],
+        ..Default::default()
+    };
+    buf.round_rect_styled(
+        Rect {
+            x: 150,
+            y: 20,
+            w: 120,
+            h: 70,
+        },
+        20,
+        &dash_dot,
+        Color::RED,
+    );
+    let dashed = StrokeStyle {
+        width: 4.,
+        dash: vec![10.],
+        ..Default::default()
+    };
+    buf.circle_arc_styled(
+        Offset::new(150, 220),
+        50,
+        0.,
+        3.,
+        &dashed,
+        Color::DARK_GREEN,
+    );
+    buf.circle_styled(Offset::new(320, 200), 50, &dashed, Color::DARK_GREEN);
+    buf.draw();
+}
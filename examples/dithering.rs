@@ -0,0 +1,38 @@
+use lite_graphics::{
+    color::{Color, DirectionalGradient, Interpolation, Pattern, Rgba, Sampling, Tiling},
+    dither::DitherMode,
+    Buffer, Drawable, Offset, Rect, Size,
+};
+
+fn main() {
+    let buf = Buffer::new(400, 300);
+    let gradient: Color = DirectionalGradient::new(
+        &[(0.0, Rgba::BLACK), (1.0, Rgba::WHITE)],
+        false,
+        Interpolation::Srgb,
+        0.,
+        360.,
+        Offset::new(0, 0),
+    )
+    .into();
+    let modes = [
+        DitherMode::None,
+        DitherMode::Bayer4,
+        DitherMode::Bayer8,
+        DitherMode::FloydSteinberg,
+        DitherMode::Atkinson,
+    ];
+    for (i, mode) in modes.into_iter().enumerate() {
+        // Render each strip separately, reduce it to 1-bit, then copy it in.
+        let strip = Buffer::new(360, 40);
+        strip.fill_rect(Size::new(360, 40).into(), gradient.clone().dither(true));
+        strip.quantize(&[Rgba::BLACK, Rgba::WHITE], mode);
+
+        let rect = Rect::new(Offset::new(20, 20 + 52 * i as i32), Size::new(360, 40));
+        buf.fill_rect(
+            rect,
+            Pattern::new(strip, rect, Tiling::Clamp, Sampling::Nearest).into(),
+        );
+    }
+    buf.draw();
+}
@@ -0,0 +1,23 @@
+use lite_graphics::{color::Color, Buffer, Drawable, Offset, Radius, Rect};
+
+fn main() {
+    let buf = Buffer::new(400, 300);
+    let radius = Radius::new(60, 30);
+    buf.ellipse(Offset::new(70, 50), radius, Color::RED);
+    buf.ellipse_aa(Offset::new(200, 50), radius, Color::BLUE);
+    buf.ellipse_arc(Offset::new(330, 50), radius, 1., 5., Color::BLACK);
+    buf.fill_ellipse(Offset::new(70, 130), radius, Color::RED);
+    buf.fill_ellipse_aa(Offset::new(200, 130), radius, Color::BLUE);
+    buf.ellipse_pie(Offset::new(330, 130), radius, 5.5, 1., Color::DARK_GREEN);
+    buf.round_rect_aa(
+        Rect {
+            x: 50,
+            y: 190,
+            w: 300,
+            h: 100,
+        },
+        Radius::new(40, 20),
+        Color::BLACK,
+    );
+    buf.draw();
+}
@@ -0,0 +1,31 @@
+use std::time::Duration;
+
+use lite_graphics::{
+    color::Color,
+    dither::{DitherMode, Quantizer},
+    image::{GifEncoder, GifPalette},
+    Buffer, Drawable, Offset,
+};
+
+fn main() {
+    let path = std::env::args().nth(1).unwrap_or("out.gif".into());
+    let mut gif = GifEncoder::new(
+        400,
+        300,
+        Some(0),
+        GifPalette::Global,
+        Quantizer::MedianCut,
+        DitherMode::None,
+    );
+    let mut last = Buffer::new(400, 300);
+    for i in 0..20i32 {
+        let buf = Buffer::new(400, 300);
+        let y = 250 - (i - 10).pow(2) * 2;
+        buf.fill_circle_aa(Offset::new(50 + i * 15, y), 20, Color::RED);
+        buf.text(Offset::new(10, 10), "Saved as GIF", Color::BLACK);
+        gif.add_frame(&buf, Duration::from_millis(50));
+        last = buf;
+    }
+    gif.save(&path).unwrap();
+    last.draw();
+}
@@ -1,5 +1,5 @@
 use lite_graphics::{
-    color::{Color, DirectionalGradient, Rgba},
+    color::{Color, DirectionalGradient, Interpolation, Rgba},
     Buffer, Drawable, Offset, Rect,
 };
 
@@ -15,6 +15,7 @@ fn main() {
         DirectionalGradient::new(
             &[(0.0, Rgba::RED), (1.0, Rgba::BLUE)],
             false,
+            Interpolation::Srgb,
             0.,
             150.,
             Offset { x: 100, y: 100 },
@@ -31,6 +32,7 @@ fn main() {
         DirectionalGradient::new(
             &[(0.0, Rgba::BLUE.set_a(0)), (1.0, Rgba::BLUE)],
             false,
+            Interpolation::Srgb,
             0.,
             150.,
             Offset { x: 150, y: 150 },
@@ -0,0 +1,31 @@
+use lite_graphics::{
+    color::{DirectionalGradient, HueDirection, Interpolation, Rgba},
+    Buffer, Drawable, Offset, Rect, Size,
+};
+
+fn main() {
+    let buf = Buffer::new(400, 300);
+    let spaces = [
+        Interpolation::Srgb,
+        Interpolation::LinearSrgb,
+        Interpolation::Oklab,
+        Interpolation::Oklch(HueDirection::Shorter),
+        Interpolation::Oklch(HueDirection::Longer),
+    ];
+    for (i, interpolation) in spaces.into_iter().enumerate() {
+        let y = 20 + 55 * i as i32;
+        buf.fill_rect(
+            Rect::new(Offset::new(20, y), Size::new(360, 45)),
+            DirectionalGradient::new(
+                &[(0.0, Rgba::RED), (1.0, Rgba::GREEN)],
+                false,
+                interpolation,
+                0.,
+                360.,
+                Offset::new(20, y),
+            )
+            .into(),
+        );
+    }
+    buf.draw();
+}
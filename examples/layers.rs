@@ -0,0 +1,49 @@
+use lite_graphics::{
+    blend::BlendMode,
+    color::{Color, Rgba},
+    layer::LayerStack,
+    Buffer, Drawable, Offset, Rect, Size,
+};
+
+fn main() {
+    let mut stack = LayerStack::new(Buffer::new(400, 300));
+    stack.background().fill_rect(
+        Rect::new(Offset::new(40, 40), Size::new(320, 220)),
+        Color::SILVER,
+    );
+
+    let window = stack.add(Rect::new(Offset::new(60, 60), Size::new(200, 150)), 0);
+    let popup = stack.add(Rect::new(Offset::new(180, 120), Size::new(160, 100)), 1);
+    let shade = stack.add(Rect::new(Offset::new(0, 0), Size::new(400, 100)), 2);
+
+    let layer = stack.get(window).unwrap();
+    layer.overlay.fill_round_rect_aa(
+        Rect::new(Offset::new(0, 0), Size::new(200, 150)),
+        10,
+        Color::BLUE,
+    );
+    let layer = stack.get(popup).unwrap();
+    layer.overlay.fill_round_rect_aa(
+        Rect::new(Offset::new(0, 0), Size::new(160, 100)),
+        10,
+        Color::RED,
+    );
+    let layer = stack.get_mut(shade).unwrap();
+    layer
+        .overlay
+        .fill_rect(Size::new(400, 100).into(), Rgba::YELLOW.into());
+    layer.blend_mode = BlendMode::Multiply;
+    layer.opacity = 160;
+    stack.composite();
+
+    // Bring the window to the front, fade it, and move the popup partly off screen.
+    let layer = stack.get_mut(window).unwrap();
+    layer.z = 3;
+    layer.opacity = 200;
+    stack
+        .get_mut(popup)
+        .unwrap()
+        .overlay
+        .offset(Offset::new(300, 220));
+    stack.composite().draw();
+}
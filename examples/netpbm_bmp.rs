@@ -0,0 +1,12 @@
+use lite_graphics::{color::Color, image::NetpbmFormat, Buffer, Drawable, Offset};
+
+fn main() {
+    let buf = Buffer::new(400, 300);
+    buf.fill_circle_aa(Offset::new(200, 150), 100, Color::BLUE);
+    buf.text(Offset::new(160, 146), "Round trip", Color::WHITE);
+
+    let ppm = Buffer::from_netpbm(&buf.to_netpbm(NetpbmFormat::Ppm)).unwrap();
+    let bmp = Buffer::from_bmp(&ppm.to_bmp()).unwrap();
+    let rle = bmp.to_bmp_rle8().unwrap_or_else(|| bmp.to_bmp());
+    Buffer::from_bmp(&rle).unwrap().draw();
+}
@@ -0,0 +1,27 @@
+use lite_graphics::{color::Color, draw::FillRule, Buffer, Drawable, Path};
+
+fn main() {
+    let buf = Buffer::new(400, 300);
+    let mut shape = Path::new();
+    shape
+        .move_to(20., 20.)
+        .line_to(120., 40.)
+        .quad_to(180., 20., 160., 120.)
+        .cubic_to(100., 200., 60., 60., 20., 140.)
+        .close();
+    buf.fill_path(&shape, FillRule::NonZero, Color::ORANGE);
+    buf.stroke_path(&shape, Color::BLACK);
+
+    let mut curve = Path::new();
+    curve
+        .move_to(200.5, 100.5)
+        .arc_to(50., 30., 0.3, false, true, 300.5, 150.5)
+        .arc_to(50., 30., 0., true, false, 250.5, 250.5);
+    buf.stroke_path(&curve, Color::BLUE);
+
+    let mut pie = Path::new();
+    pie.arc(300., 60., 50., 30., 0.5, 4.).close();
+    buf.fill_path(&pie, FillRule::EvenOdd, Color::GREEN.set_a(127));
+    buf.stroke_path(&pie, Color::DARK_GREEN);
+    buf.draw();
+}
@@ -0,0 +1,44 @@
+use lite_graphics::{
+    color::{Color, Pattern, Sampling, Tiling},
+    Buffer, Drawable, Offset, Rect, Size,
+};
+
+fn main() {
+    // A small checkerboard texture.
+    let texture = Buffer::new(16, 16);
+    texture.fill_rect(Rect::new(Offset::new(0, 0), Size::new(8, 8)), Color::SILVER);
+    texture.fill_rect(Rect::new(Offset::new(8, 8), Size::new(8, 8)), Color::SILVER);
+    texture.fill_circle_aa(Offset::new(8, 8), 3, Color::RED);
+
+    let buf = Buffer::new(400, 300);
+    buf.fill_rect(
+        Rect::new(Offset::new(0, 0), Size::new(400, 300)),
+        Pattern::new(
+            texture.clone(),
+            Rect::new(Offset::new(0, 0), Size::new(16, 16)),
+            Tiling::Repeat,
+            Sampling::Nearest,
+        )
+        .into(),
+    );
+
+    // The texture stretched into a rounded avatar.
+    let avatar = Rect::new(Offset::new(40, 40), Size::new(120, 120));
+    buf.fill_round_rect_aa(
+        avatar,
+        30,
+        Pattern::new(texture.clone(), avatar, Tiling::Clamp, Sampling::Bilinear).into(),
+    );
+
+    buf.fill_rect(
+        Rect::new(Offset::new(200, 40), Size::new(160, 220)),
+        Pattern::new(
+            texture,
+            Rect::new(Offset::new(200, 100), Size::new(32, 32)),
+            Tiling::RepeatX,
+            Sampling::Bilinear,
+        )
+        .into(),
+    );
+    buf.draw();
+}
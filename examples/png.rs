@@ -0,0 +1,12 @@
+use lite_graphics::{color::Color, Buffer, Drawable, Offset};
+
+fn main() {
+    let path = std::env::args().nth(1).unwrap_or("out.png".into());
+    let buf = Buffer::new(400, 300);
+    buf.fill_circle_aa(Offset::new(200, 150), 100, Color::RED);
+    buf.text(Offset::new(150, 146), "Saved as PNG", Color::WHITE);
+    buf.save_png(&path).unwrap();
+
+    let loaded = Buffer::from_png(&std::fs::read(&path).unwrap()).unwrap();
+    loaded.draw();
+}
@@ -0,0 +1,23 @@
+use lite_graphics::{color::Color, draw::FillRule, Buffer, Drawable, Offset};
+
+fn main() {
+    let buf = Buffer::new(400, 300);
+    let star = |cx: i32, cy: i32| {
+        [(0, -80), (47, 65), (-76, -25), (76, -25), (-47, 65)]
+            .map(|(x, y)| Offset::new(cx + x, cy + y))
+    };
+    buf.fill_polygon(&star(100, 150), FillRule::EvenOdd, Color::RED);
+    buf.fill_polygon_aa(&star(300, 150), FillRule::NonZero, Color::BLUE);
+    buf.fill_polygon_aa(
+        &[
+            Offset::new(150, 20),
+            Offset::new(250, 20),
+            Offset::new(200, 60),
+            Offset::new(250, 100),
+            Offset::new(150, 100),
+        ],
+        FillRule::EvenOdd,
+        Color::DARK_GREEN.set_a(127),
+    );
+    buf.draw();
+}
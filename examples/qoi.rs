@@ -0,0 +1,11 @@
+use lite_graphics::{color::Color, image::QoiColorspace, Buffer, Drawable, Offset};
+
+fn main() {
+    let buf = Buffer::new(400, 300);
+    buf.fill_circle_aa(Offset::new(200, 150), 100, Color::GREEN);
+    buf.text(Offset::new(148, 146), "Encoded as QOI", Color::BLACK);
+
+    let data = buf.encode_qoi(QoiColorspace::Srgb);
+    let (buf, _) = Buffer::decode_qoi(&data).unwrap();
+    buf.draw();
+}
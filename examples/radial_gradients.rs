@@ -0,0 +1,49 @@
+use lite_graphics::{
+    color::{Interpolation, RadialGradient, Rgba},
+    Buffer, Drawable, Offset, Rect, Size,
+};
+
+fn main() {
+    let buf = Buffer::new(400, 300);
+    let stops = [(0.0, Rgba::WHITE), (0.5, Rgba::YELLOW), (1.0, Rgba::RED)];
+    buf.fill_circle_aa(
+        Offset::new(100, 100),
+        80,
+        RadialGradient::new(
+            &stops,
+            false,
+            Interpolation::Srgb,
+            Offset::new(100, 100),
+            80.,
+            None,
+        )
+        .into(),
+    );
+    buf.fill_circle_aa(
+        Offset::new(300, 100),
+        80,
+        RadialGradient::new(
+            &stops,
+            false,
+            Interpolation::Srgb,
+            Offset::new(300, 100),
+            80.,
+            Some(Offset::new(270, 70)),
+        )
+        .into(),
+    );
+    buf.fill_round_rect_aa(
+        Rect::new(Offset::new(50, 200), Size::new(300, 80)),
+        16,
+        RadialGradient::new(
+            &[(0.0, Rgba::BLUE), (0.5, Rgba::CYAN), (1.0, Rgba::BLUE)],
+            true,
+            Interpolation::Srgb,
+            Offset::new(200, 240),
+            30.,
+            None,
+        )
+        .into(),
+    );
+    buf.draw();
+}
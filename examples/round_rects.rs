@@ -42,5 +42,16 @@ fn main() {
         10,
         Color::BLACK.set_a(127),
     );
+    // A tab, with rounded top corners only.
+    buf.fill_round_rect_aa(
+        Rect {
+            x: 150,
+            y: 20,
+            w: 100,
+            h: 30,
+        },
+        [10, 10, 0, 0],
+        Color::DARK_BLUE,
+    );
     buf.draw();
 }
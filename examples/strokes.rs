@@ -0,0 +1,69 @@
+use lite_graphics::{
+    color::Color, Buffer, Drawable, LineCap, LineJoin, Offset, Path, Rect, StrokeStyle,
+};
+
+fn main() {
+    let buf = Buffer::new(400, 300);
+    for (i, cap) in [LineCap::Butt, LineCap::Round, LineCap::Square]
+        .into_iter()
+        .enumerate()
+    {
+        let y = 20 + 40 * i as i32;
+        let style = StrokeStyle::new(10., cap, LineJoin::Miter);
+        buf.line_styled(
+            Offset::new(20, y),
+            Offset::new(120, y + 20),
+            &style,
+            Color::BLUE,
+        );
+    }
+    for (i, join) in [LineJoin::Miter, LineJoin::Round, LineJoin::Bevel]
+        .into_iter()
+        .enumerate()
+    {
+        let x = 150. + 80. * i as f32;
+        let mut path = Path::new();
+        path.move_to(x, 150.)
+            .line_to(x + 30., 30.)
+            .line_to(x + 60., 150.);
+        let style = StrokeStyle {
+            miter_limit: 10.,
+            ..StrokeStyle::new(12., LineCap::Butt, join)
+        };
+        buf.stroke_path_styled(&path, &style, Color::DARK_GREEN);
+    }
+    let border = StrokeStyle {
+        width: 3.,
+        ..Default::default()
+    };
+    buf.rect_styled(
+        Rect {
+            x: 20,
+            y: 160,
+            w: 100,
+            h: 60,
+        },
+        &border,
+        Color::BLACK,
+    );
+    buf.round_rect_styled(
+        Rect {
+            x: 140,
+            y: 180,
+            w: 100,
+            h: 60,
+        },
+        15,
+        &border,
+        Color::BLACK,
+    );
+    buf.circle_arc_styled(
+        Offset::new(320, 230),
+        40,
+        5.,
+        2.,
+        &StrokeStyle::new(8., LineCap::Round, LineJoin::Round),
+        Color::MAGENTA,
+    );
+    buf.draw();
+}
@@ -0,0 +1,14 @@
+use lite_graphics::{color::Color, text, Buffer, Drawable, Offset, Rect, Size};
+
+fn main() {
+    let buf = Buffer::new(400, 300);
+    let label = "Hello, world!\nSecond line";
+    let pos = Offset::new(100, 100);
+    let size = text::measure_text(label);
+    buf.fill_rect(
+        Rect::new(pos - Offset::new(4, 4), size + Size::new(8, 8)),
+        Color::SILVER,
+    );
+    buf.text(pos, label, Color::BLACK);
+    buf.draw();
+}
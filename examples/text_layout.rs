@@ -0,0 +1,40 @@
+use lite_graphics::{
+    color::Color,
+    text::{BuiltinFont, TextAlign, TextStyle, VerticalAlign},
+    Buffer, Drawable, Offset, Rect, Size,
+};
+
+fn main() {
+    let buf = Buffer::new(400, 300);
+    let text =
+        "The quick brown fox jumps over the lazy dog. Pack my box with five dozen liquor jugs.";
+    let styles = [
+        (TextAlign::Left, VerticalAlign::Top),
+        (TextAlign::Center, VerticalAlign::Middle),
+        (TextAlign::Right, VerticalAlign::Bottom),
+        (TextAlign::Justify, VerticalAlign::Top),
+    ];
+    for (i, (align, vertical_align)) in styles.into_iter().enumerate() {
+        let rect = Rect::new(
+            Offset::new(10 + 195 * (i as i32 % 2), 10 + 110 * (i as i32 / 2)),
+            Size::new(185, 100),
+        );
+        buf.rect(rect, Color::GRAY);
+        let style = TextStyle {
+            align,
+            vertical_align,
+            ..Default::default()
+        };
+        buf.text_in_rect(rect, text, style);
+    }
+
+    let rect = Rect::new(Offset::new(10, 240), Size::new(380, 24));
+    buf.rect(rect, Color::GRAY);
+    let style = TextStyle {
+        line_height: 1.5,
+        ellipsis: true,
+        ..TextStyle::new(&BuiltinFont, Color::DARK_BLUE)
+    };
+    buf.text_in_rect(rect, text, style);
+    buf.draw();
+}
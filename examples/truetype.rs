@@ -0,0 +1,19 @@
+use lite_graphics::{
+    color::Color,
+    text::{Font, TrueTypeFont},
+    Buffer, Drawable, Offset,
+};
+
+fn main() {
+    let path = std::env::args().nth(1).expect("usage: truetype <FONT.ttf>");
+    let font = TrueTypeFont::parse(std::fs::read(path).unwrap()).unwrap();
+
+    let buf = Buffer::new(400, 300);
+    let mut y = 20;
+    for size in [12., 16., 24., 36., 48.] {
+        let scaled = font.scaled(size);
+        buf.text_font(Offset::new(20, y), "AVATAR Wave", &scaled, Color::BLACK);
+        y += scaled.line_height();
+    }
+    buf.draw();
+}
@@ -0,0 +1,193 @@
+use crate::color::Rgba;
+
+/// How drawn colors mix with what's already there, from the W3C compositing spec.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum BlendMode {
+    /// Plain alpha blending.
+    #[default]
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+    /// Hue of the source, saturation and luminosity of the backdrop.
+    Hue,
+    /// Saturation of the source, hue and luminosity of the backdrop.
+    Saturation,
+    /// Hue and saturation of the source, luminosity of the backdrop.
+    Color,
+    /// Luminosity of the source, hue and saturation of the backdrop.
+    Luminosity,
+}
+
+fn lum([r, g, b]: [f32; 3]) -> f32 {
+    0.3 * r + 0.59 * g + 0.11 * b
+}
+
+fn set_lum(c: [f32; 3], l: f32) -> [f32; 3] {
+    let d = l - lum(c);
+    let c = c.map(|v| v + d);
+    // Clip into range, keeping luminosity.
+    let l = lum(c);
+    let (min, max) = (c[0].min(c[1]).min(c[2]), c[0].max(c[1]).max(c[2]));
+    if min < 0. {
+        c.map(|v| l + (v - l) * l / (l - min))
+    } else if max > 1. {
+        c.map(|v| l + (v - l) * (1. - l) / (max - l))
+    } else {
+        c
+    }
+}
+
+fn sat([r, g, b]: [f32; 3]) -> f32 {
+    r.max(g).max(b) - r.min(g).min(b)
+}
+
+fn set_sat(c: [f32; 3], s: f32) -> [f32; 3] {
+    let mut i = [0, 1, 2];
+    i.sort_by(|&a, &b| c[a].total_cmp(&c[b]));
+    let [min, mid, max] = i;
+    let mut out = [0.; 3];
+    if c[max] > c[min] {
+        out[mid] = (c[mid] - c[min]) * s / (c[max] - c[min]);
+        out[max] = s;
+    }
+    out
+}
+
+fn hard_light(cb: f32, cs: f32) -> f32 {
+    if cs <= 0.5 {
+        cb * 2. * cs
+    } else {
+        let cs = 2. * cs - 1.;
+        cb + cs - cb * cs
+    }
+}
+
+impl BlendMode {
+    /// Mixes source and backdrop colors, with channels 0-1.
+    pub(crate) fn mix(self, cb: [f32; 3], cs: [f32; 3]) -> [f32; 3] {
+        let separable = |f: fn(f32, f32) -> f32| [0, 1, 2].map(|i| f(cb[i], cs[i]));
+        match self {
+            Self::Normal => cs,
+            Self::Multiply => separable(|cb, cs| cb * cs),
+            Self::Screen => separable(|cb, cs| cb + cs - cb * cs),
+            Self::Overlay => separable(|cb, cs| hard_light(cs, cb)),
+            Self::Darken => separable(f32::min),
+            Self::Lighten => separable(f32::max),
+            Self::ColorDodge => separable(|cb, cs| {
+                if cb == 0. {
+                    0.
+                } else if cs >= 1. {
+                    1.
+                } else {
+                    (cb / (1. - cs)).min(1.)
+                }
+            }),
+            Self::ColorBurn => separable(|cb, cs| {
+                if cb >= 1. {
+                    1.
+                } else if cs == 0. {
+                    0.
+                } else {
+                    1. - ((1. - cb) / cs).min(1.)
+                }
+            }),
+            Self::HardLight => separable(hard_light),
+            Self::SoftLight => separable(|cb, cs| {
+                if cs <= 0.5 {
+                    cb - (1. - 2. * cs) * cb * (1. - cb)
+                } else {
+                    let d = if cb <= 0.25 {
+                        ((16. * cb - 12.) * cb + 4.) * cb
+                    } else {
+                        cb.sqrt()
+                    };
+                    cb + (2. * cs - 1.) * (d - cb)
+                }
+            }),
+            Self::Difference => separable(|cb, cs| (cb - cs).abs()),
+            Self::Exclusion => separable(|cb, cs| cb + cs - 2. * cb * cs),
+            Self::Hue => set_lum(set_sat(cs, sat(cb)), lum(cb)),
+            Self::Saturation => set_lum(set_sat(cb, sat(cs)), lum(cb)),
+            Self::Color => set_lum(cs, lum(cb)),
+            Self::Luminosity => set_lum(cb, lum(cs)),
+        }
+    }
+
+    /// Blends a color into an opaque RGB pixel.
+    pub(crate) fn blend_rgb(self, dst: &mut [u8], src: Rgba) {
+        let cb = [dst[0], dst[1], dst[2]].map(|c| c as f32 / 255.);
+        let cs = [src.r, src.g, src.b].map(|c| c as f32 / 255.);
+        let alpha = src.a as f32 / 255.;
+        let mixed = self.mix(cb, cs);
+        for i in 0..3 {
+            dst[i] = ((cb[i] + (mixed[i] - cb[i]) * alpha) * 255.).round() as u8;
+        }
+    }
+
+    /// Blends a color into a premultiplied RGBA pixel.
+    pub(crate) fn blend_premultiplied(self, dst: &mut [u8], src: Rgba) {
+        let ab = dst[3] as f32 / 255.;
+        let premultiplied = [dst[0], dst[1], dst[2]].map(|c| c as f32 / 255.);
+        let cb = premultiplied.map(|c| if ab > 0. { (c / ab).min(1.) } else { 0. });
+        let cs = [src.r, src.g, src.b].map(|c| c as f32 / 255.);
+        let alpha = src.a as f32 / 255.;
+        let mixed = self.mix(cb, cs);
+        for i in 0..3 {
+            // Where the backdrop is transparent, the source shows unmixed.
+            let cs = (1. - ab) * cs[i] + ab * mixed[i];
+            dst[i] = ((alpha * cs + (1. - alpha) * premultiplied[i]) * 255.).round() as u8;
+        }
+        dst[3] = ((alpha + ab * (1. - alpha)) * 255.).round() as u8;
+    }
+}
+
+/// Porter-Duff operators for compositing a source onto a destination.
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum CompositeOp {
+    Clear,
+    Src,
+    Dst,
+    #[default]
+    SrcOver,
+    DstOver,
+    SrcIn,
+    DstIn,
+    SrcOut,
+    DstOut,
+    SrcAtop,
+    DstAtop,
+    Xor,
+    /// Adds source and destination, saturating.
+    Plus,
+}
+
+impl CompositeOp {
+    /// Fractions of the source and destination that are kept, 0-255, from their alphas.
+    pub(crate) fn factors(self, src_a: u8, dst_a: u8) -> (u8, u8) {
+        let (sa, da) = (src_a, dst_a);
+        match self {
+            Self::Clear => (0, 0),
+            Self::Src => (255, 0),
+            Self::Dst => (0, 255),
+            Self::SrcOver => (255, 255 - sa),
+            Self::DstOver => (255 - da, 255),
+            Self::SrcIn => (da, 0),
+            Self::DstIn => (0, sa),
+            Self::SrcOut => (255 - da, 0),
+            Self::DstOut => (0, 255 - sa),
+            Self::SrcAtop => (da, 255 - sa),
+            Self::DstAtop => (255 - da, sa),
+            Self::Xor => (255 - da, 255 - sa),
+            Self::Plus => (255, 255),
+        }
+    }
+}
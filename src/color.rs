@@ -1,9 +1,18 @@
-use crate::Offset;
+use std::f32::consts::{PI, TAU};
+
+use crate::{dither::bayer, Buffer, Offset, Rect};
+
+mod parse;
+
+pub use parse::ColorParseError;
 
 #[derive(Clone)]
 pub enum Color {
     Rgba(Rgba),
     DirectionalGradient(DirectionalGradient),
+    RadialGradient(RadialGradient),
+    ConicGradient(ConicGradient),
+    Pattern(Pattern),
 }
 
 impl Color {
@@ -29,7 +38,7 @@ impl Color {
     pub const TRANSPARENT: Self = Self::Rgba(Rgba::TRANSPARENT);
 
     /// Parse hex string
-    pub const fn hex(val: &'static str) -> Option<Self> {
+    pub const fn hex(val: &str) -> Option<Self> {
         match Rgba::hex(val) {
             Some(x) => Some(Self::Rgba(x)),
             None => None,
@@ -40,23 +49,52 @@ impl Color {
         match self {
             Self::Rgba(rgba) => rgba.get(pos),
             Self::DirectionalGradient(grad) => grad.get(pos),
+            Self::RadialGradient(grad) => grad.get(pos),
+            Self::ConicGradient(grad) => grad.get(pos),
+            Self::Pattern(pattern) => pattern.get(pos),
         }
     }
     pub fn set_a(&self, a: u8) -> Self {
         match self {
             Self::Rgba(rgba) => Self::Rgba(rgba.set_a(a)),
             Self::DirectionalGradient(grad) => Self::DirectionalGradient(grad.set_a(a)),
+            Self::RadialGradient(grad) => Self::RadialGradient(grad.set_a(a)),
+            Self::ConicGradient(grad) => Self::ConicGradient(grad.set_a(a)),
+            Self::Pattern(pattern) => Self::Pattern(pattern.set_a(a)),
         }
     }
 
-    /// Maps an Rgba value if one.
+    /// Maps an Rgba value, or the colors of a gradient.
     pub fn map<F: Fn(Rgba) -> Rgba>(self, f: F) -> Self {
         match self {
             Self::Rgba(rgba) => Self::Rgba(f(rgba)),
+            Self::DirectionalGradient(grad) => Self::DirectionalGradient(DirectionalGradient {
+                base: grad.base.map(f),
+                ..grad
+            }),
+            Self::RadialGradient(grad) => Self::RadialGradient(RadialGradient {
+                base: grad.base.map(f),
+                ..grad
+            }),
+            Self::ConicGradient(grad) => Self::ConicGradient(ConicGradient {
+                base: grad.base.map(f),
+                ..grad
+            }),
             x => x,
         }
     }
 
+    /// Enables or disables ordered dithering of a gradient's color channels, to hide banding. Alpha isn't dithered, and other colors are unchanged.
+    pub fn dither(mut self, dither: bool) -> Self {
+        match &mut self {
+            Self::DirectionalGradient(DirectionalGradient { base, .. })
+            | Self::RadialGradient(RadialGradient { base, .. })
+            | Self::ConicGradient(ConicGradient { base, .. }) => base.dither = dither,
+            _ => {}
+        }
+        self
+    }
+
     pub fn get_rgba(&self) -> Option<Rgba> {
         if let Self::Rgba(rgba) = self {
             Some(*rgba)
@@ -78,6 +116,24 @@ impl From<DirectionalGradient> for Color {
     }
 }
 
+impl From<RadialGradient> for Color {
+    fn from(value: RadialGradient) -> Self {
+        Self::RadialGradient(value)
+    }
+}
+
+impl From<ConicGradient> for Color {
+    fn from(value: ConicGradient) -> Self {
+        Self::ConicGradient(value)
+    }
+}
+
+impl From<Pattern> for Color {
+    fn from(value: Pattern) -> Self {
+        Self::Pattern(value)
+    }
+}
+
 #[derive(Clone, Copy)]
 pub struct Rgba {
     pub r: u8,
@@ -142,7 +198,7 @@ impl Rgba {
     pub const WHITE: Self = Self::hex("#fff").unwrap();
     pub const TRANSPARENT: Self = Self::hex("#0000").unwrap();
     // Parse hex string (const fn).
-    pub const fn hex(val: &'static str) -> Option<Self> {
+    pub const fn hex(val: &str) -> Option<Self> {
         const fn hex_decode(n1: u8, n2: u8) -> Option<u8> {
             if n1 < 0x30 || n2 < 0x30 {
                 return None;
@@ -163,7 +219,7 @@ impl Rgba {
         }
 
         let bytes = val.as_bytes();
-        if bytes[0] != 35 {
+        if bytes.is_empty() || bytes[0] != 35 {
             return None;
         }
 
@@ -245,26 +301,271 @@ impl Rgba {
     fn get(&self, _pos: Offset) -> Rgba {
         *self
     }
+    fn from_rgb_f32(r: f32, g: f32, b: f32) -> Self {
+        let channel = |c: f32| (c.clamp(0., 1.) * 255.).round() as u8;
+        Self {
+            r: channel(r),
+            g: channel(g),
+            b: channel(b),
+            a: 255,
+        }
+    }
+    /// Hue in degrees, and the min and max of R,G,B, all 0-1.
+    fn hue_min_max(&self) -> (f32, f32, f32) {
+        let [r, g, b] = [self.r, self.g, self.b].map(|c| c as f32 / 255.);
+        let (min, max) = (r.min(g).min(b), r.max(g).max(b));
+        let d = max - min;
+        let h = if d == 0. {
+            0.
+        } else if max == r {
+            (g - b) / d
+        } else if max == g {
+            (b - r) / d + 2.
+        } else {
+            (r - g) / d + 4.
+        };
+        ((h * 60.).rem_euclid(360.), min, max)
+    }
+
+    /// Hue in degrees, saturation and lightness 0-1. Opaque.
+    pub fn from_hsl(h: f32, s: f32, l: f32) -> Self {
+        let (s, l) = (s.clamp(0., 1.), l.clamp(0., 1.));
+        let a = s * l.min(1. - l);
+        let f = |n: f32| {
+            let k = (n + h / 30.).rem_euclid(12.);
+            l - a * (k - 3.).min(9. - k).clamp(-1., 1.)
+        };
+        Self::from_rgb_f32(f(0.), f(8.), f(4.))
+    }
+    /// Hue in degrees, saturation and value 0-1. Opaque.
+    pub fn from_hsv(h: f32, s: f32, v: f32) -> Self {
+        let (s, v) = (s.clamp(0., 1.), v.clamp(0., 1.));
+        let f = |n: f32| {
+            let k = (n + h / 60.).rem_euclid(6.);
+            v - v * s * k.min(4. - k).clamp(0., 1.)
+        };
+        Self::from_rgb_f32(f(5.), f(3.), f(1.))
+    }
+    /// Hue in degrees, whiteness and blackness 0-1. Opaque.
+    pub fn from_hwb(h: f32, w: f32, b: f32) -> Self {
+        let (w, b) = (w.clamp(0., 1.), b.clamp(0., 1.));
+        if w + b >= 1. {
+            let gray = w / (w + b);
+            return Self::from_rgb_f32(gray, gray, gray);
+        }
+        let pure = Self::from_hsl(h, 1., 0.5);
+        let f = |c: u8| c as f32 / 255. * (1. - w - b) + w;
+        Self::from_rgb_f32(f(pure.r), f(pure.g), f(pure.b))
+    }
+    /// Lightness 0-1, chroma (0-0.4 in practice) and hue in degrees. Opaque, clamped to sRGB.
+    pub fn from_oklch(l: f32, c: f32, h: f32) -> Self {
+        let h = h.to_radians();
+        let [r, g, b] = oklab_to_linear([l, c * h.cos(), c * h.sin()]);
+        Self::from_rgb_f32(
+            from_linear(r) / 255.,
+            from_linear(g) / 255.,
+            from_linear(b) / 255.,
+        )
+    }
+
+    /// Get hue in degrees, saturation and lightness 0-1.
+    pub fn to_hsl(&self) -> (f32, f32, f32) {
+        let (h, min, max) = self.hue_min_max();
+        let l = (max + min) / 2.;
+        let s = if max == min {
+            0.
+        } else {
+            (max - min) / (1. - (2. * l - 1.).abs())
+        };
+        (h, s, l)
+    }
+    /// Get hue in degrees, saturation and value 0-1.
+    pub fn to_hsv(&self) -> (f32, f32, f32) {
+        let (h, min, max) = self.hue_min_max();
+        let s = if max == 0. { 0. } else { (max - min) / max };
+        (h, s, max)
+    }
+    /// Get hue in degrees, whiteness and blackness 0-1.
+    pub fn to_hwb(&self) -> (f32, f32, f32) {
+        let (h, min, max) = self.hue_min_max();
+        (h, min, 1. - max)
+    }
+    /// Get lightness, chroma and hue in degrees.
+    pub fn to_oklch(&self) -> (f32, f32, f32) {
+        let [l, a, b] = linear_to_oklab([to_linear(self.r), to_linear(self.g), to_linear(self.b)]);
+        (l, a.hypot(b), b.atan2(a).to_degrees().rem_euclid(360.))
+    }
+
+    /// Adds to the HSL lightness, keeping alpha.
+    pub fn lighten(self, amount: f32) -> Self {
+        let (h, s, l) = self.to_hsl();
+        Self {
+            a: self.a,
+            ..Self::from_hsl(h, s, l + amount)
+        }
+    }
+    /// Subtracts from the HSL lightness, keeping alpha.
+    pub fn darken(self, amount: f32) -> Self {
+        self.lighten(-amount)
+    }
+    /// Adds to the HSL saturation, keeping alpha. Negative amounts desaturate.
+    pub fn saturate(self, amount: f32) -> Self {
+        let (h, s, l) = self.to_hsl();
+        Self {
+            a: self.a,
+            ..Self::from_hsl(h, s + amount, l)
+        }
+    }
+    /// Rotates the hue by some degrees, keeping alpha.
+    pub fn rotate_hue(self, degrees: f32) -> Self {
+        let (h, s, l) = self.to_hsl();
+        Self {
+            a: self.a,
+            ..Self::from_hsl(h + degrees, s, l)
+        }
+    }
+}
+
+/// Which way hues go around the color wheel when interpolating in [`Interpolation::Oklch`].
+#[derive(Clone, Copy, Default)]
+pub enum HueDirection {
+    #[default]
+    Shorter,
+    Longer,
+    Increasing,
+    Decreasing,
+}
+
+/// Color space in which gradients interpolate between colors.
+#[derive(Clone, Copy, Default)]
+pub enum Interpolation {
+    /// Gamma-encoded sRGB, like [`Rgba::lerp`].
+    #[default]
+    Srgb,
+    LinearSrgb,
+    Oklab,
+    Oklch(HueDirection),
+}
+
+/// Decodes an sRGB channel to linear light, 0-1.
+fn to_linear(c: u8) -> f32 {
+    let c = c as f32 / 255.;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Encodes linear light to an sRGB channel, 0-255, clamping out of gamut values.
+fn from_linear(c: f32) -> f32 {
+    let c = c.clamp(0., 1.);
+    let c = if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1. / 2.4) - 0.055
+    };
+    c * 255.
+}
+
+fn linear_to_oklab([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let l = (0.4122215 * r + 0.5363325 * g + 0.051446 * b).cbrt();
+    let m = (0.2119035 * r + 0.6806996 * g + 0.107397 * b).cbrt();
+    let s = (0.0883025 * r + 0.2817188 * g + 0.6299787 * b).cbrt();
+    [
+        0.2104543 * l + 0.7936178 * m - 0.004072 * s,
+        1.9779985 * l - 2.4285922 * m + 0.4505937 * s,
+        0.025904 * l + 0.7827718 * m - 0.8086758 * s,
+    ]
+}
+
+fn oklab_to_linear([l, a, b]: [f32; 3]) -> [f32; 3] {
+    let l_ = (l + 0.3963378 * a + 0.2158038 * b).powi(3);
+    let m_ = (l - 0.1055613 * a - 0.0638542 * b).powi(3);
+    let s_ = (l - 0.0894842 * a - 1.2914855 * b).powi(3);
+    [
+        4.0767417 * l_ - 3.3077116 * m_ + 0.2309699 * s_,
+        -1.268438 * l_ + 2.6097574 * m_ - 0.3413194 * s_,
+        -0.0041961 * l_ - 0.7034186 * m_ + 1.7076147 * s_,
+    ]
+}
+
+impl Interpolation {
+    /// Interpolates between two colors, `t` going from 0 to 1, into unrounded channels from 0 to 255. Alpha is always interpolated linearly.
+    fn lerp(self, from: Rgba, to: Rgba, t: f32) -> [f32; 4] {
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+        let a = mix(from.a as f32, to.a as f32);
+        let linear = |c: Rgba| [to_linear(c.r), to_linear(c.g), to_linear(c.b)];
+        let [r, g, b] = match self {
+            Self::Srgb => {
+                return [
+                    mix(from.r as f32, to.r as f32),
+                    mix(from.g as f32, to.g as f32),
+                    mix(from.b as f32, to.b as f32),
+                    a,
+                ]
+            }
+            Self::LinearSrgb => {
+                let (from, to) = (linear(from), linear(to));
+                [0, 1, 2].map(|i| mix(from[i], to[i]))
+            }
+            Self::Oklab => {
+                let (from, to) = (linear_to_oklab(linear(from)), linear_to_oklab(linear(to)));
+                oklab_to_linear([0, 1, 2].map(|i| mix(from[i], to[i])))
+            }
+            Self::Oklch(direction) => {
+                let [l1, a1, b1] = linear_to_oklab(linear(from));
+                let [l2, a2, b2] = linear_to_oklab(linear(to));
+                let (c1, c2) = (a1.hypot(b1), a2.hypot(b2));
+                let (mut h1, mut h2) = (b1.atan2(a1), b2.atan2(a2));
+                // Grays have no hue, and take the other color's.
+                if c1 < 1e-3 {
+                    h1 = h2;
+                } else if c2 < 1e-3 {
+                    h2 = h1;
+                }
+                let mut dh = h2 - h1;
+                match direction {
+                    HueDirection::Shorter if dh > PI => dh -= TAU,
+                    HueDirection::Shorter if dh < -PI => dh += TAU,
+                    HueDirection::Longer if dh > 0. && dh < PI => dh -= TAU,
+                    HueDirection::Longer if dh <= 0. && dh > -PI => dh += TAU,
+                    HueDirection::Increasing if dh < 0. => dh += TAU,
+                    HueDirection::Decreasing if dh > 0. => dh -= TAU,
+                    _ => {}
+                }
+                let (c, h) = (mix(c1, c2), h1 + dh * t);
+                oklab_to_linear([mix(l1, l2), c * h.cos(), c * h.sin()])
+            }
+        };
+        [from_linear(r), from_linear(g), from_linear(b), a]
+    }
 }
 
 #[derive(Clone)]
 pub struct GradientBase {
     steps: Vec<(u16, Rgba)>,
     repeating: bool,
+    interpolation: Interpolation,
+    dither: bool,
 }
 
 impl GradientBase {
     /// Color values are sorted, and ads both end values if missing.
-    fn new(colors: &[(u16, Rgba)], repeating: bool) -> Self {
+    fn new(colors: &[(u16, Rgba)], repeating: bool, interpolation: Interpolation) -> Self {
         let mut steps = colors.to_vec();
         if steps.len() == 1 {
             return Self {
                 repeating,
+                interpolation,
+                dither: false,
                 steps: vec![(0, steps[0].1), (u16::MAX, steps[0].1)],
             };
         } else if steps.is_empty() {
             return Self {
                 repeating,
+                interpolation,
+                dither: false,
                 steps: vec![(0, Rgba::BLACK), (u16::MAX, Rgba::BLACK)],
             };
         }
@@ -278,11 +579,36 @@ impl GradientBase {
         if steps[0].0 != 0 {
             steps.insert(0, (0, steps[0].1));
         }
-        Self { steps, repeating }
+        Self {
+            steps,
+            repeating,
+            interpolation,
+            dither: false,
+        }
+    }
+
+    /// Color values are clamped to 0-1.
+    fn from_stops(colors: &[(f32, Rgba)], repeating: bool, interpolation: Interpolation) -> Self {
+        let colors = colors
+            .iter()
+            .filter_map(|(v, c)| {
+                if !v.is_finite() || *v < 0.0 || *v > 1.0 {
+                    None
+                } else {
+                    Some(((v * u16::MAX as f32) as u16, *c))
+                }
+            })
+            .collect::<Vec<_>>();
+        Self::new(&colors, repeating, interpolation)
+    }
+
+    fn map<F: Fn(Rgba) -> Rgba>(&self, f: F) -> Self {
+        let steps = self.steps.iter().map(|c| (c.0, f(c.1))).collect();
+        GradientBase { steps, ..*self }
     }
 
     /// Get a value from the gradient
-    fn get(&self, v: f32) -> Rgba {
+    fn get(&self, v: f32, pos: Offset) -> Rgba {
         if !v.is_finite() {
             return Rgba::BLACK;
         }
@@ -306,9 +632,24 @@ impl GradientBase {
             Err(i) => {
                 let over = self.steps[i];
                 let under = self.steps[i - 1];
-                let lerp_t =
-                    ((v_c - under.0 as f32) / (over.0 as f32 - under.0 as f32) * 255.0) as u8;
-                under.1.lerp(over.1, lerp_t)
+                let t = (v_c - under.0 as f32) / (over.0 as f32 - under.0 as f32);
+                if !self.dither && matches!(self.interpolation, Interpolation::Srgb) {
+                    return under.1.lerp(over.1, (t * 255.) as u8);
+                }
+                let [r, g, b, a] = self.interpolation.lerp(under.1, over.1, t);
+                let quantize = |c: f32| {
+                    if self.dither {
+                        (c + bayer(pos.x, pos.y, 3)).floor().clamp(0., 255.) as u8
+                    } else {
+                        c.round() as u8
+                    }
+                };
+                Rgba {
+                    r: quantize(r),
+                    g: quantize(g),
+                    b: quantize(b),
+                    a: a.round() as u8,
+                }
             }
         }
     }
@@ -327,22 +668,12 @@ impl DirectionalGradient {
     pub fn new(
         colors: &[(f32, Rgba)],
         repeating: bool,
+        interpolation: Interpolation,
         angle: f32,
         scale: f32,
         offset: Offset,
     ) -> Self {
-        let colors = colors
-            .iter()
-            .filter_map(|(v, c)| {
-                if !v.is_finite() || *v < 0.0 || *v > 1.0 {
-                    None
-                } else {
-                    Some(((v * u16::MAX as f32) as u16, *c))
-                }
-            })
-            .collect::<Vec<_>>();
-
-        let base = GradientBase::new(&colors, repeating);
+        let base = GradientBase::from_stops(colors, repeating, interpolation);
         Self {
             base,
             angle: angle.rem_euclid(std::f32::consts::TAU),
@@ -354,17 +685,235 @@ impl DirectionalGradient {
         let (sin, cos) = self.angle.sin_cos();
         let real_x = (pos.x - self.offset.x) as f32 * cos - (pos.y - self.offset.y) as f32 * sin;
         let value = real_x / self.scale;
-        self.base.get(value)
+        self.base.get(value, pos)
     }
     fn set_a(&self, a: u8) -> Self {
-        let steps = self
-            .base
-            .steps
-            .iter()
-            .map(|c| (c.0, c.1.set_a(a)))
-            .collect();
         DirectionalGradient {
-            base: GradientBase { steps, ..self.base },
+            base: self.base.map(|c| c.set_a(a)),
+            ..self.clone()
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RadialGradient {
+    base: GradientBase,
+    center: Offset,
+    radius: f32,
+    focal: Option<Offset>,
+}
+
+impl RadialGradient {
+    /// Color values are sorted and clamped to 0-1, going from the focal point to the circle at `radius` pixels around `center`.
+    /// The focal point defaults to the center, and is moved inside the circle if outside.
+    pub fn new(
+        colors: &[(f32, Rgba)],
+        repeating: bool,
+        interpolation: Interpolation,
+        center: Offset,
+        radius: f32,
+        focal: Option<Offset>,
+    ) -> Self {
+        Self {
+            base: GradientBase::from_stops(colors, repeating, interpolation),
+            center,
+            radius,
+            focal,
+        }
+    }
+    fn get(&self, pos: Offset) -> Rgba {
+        let (dx, dy) = (
+            (pos.x - self.center.x) as f32,
+            (pos.y - self.center.y) as f32,
+        );
+        let Some(focal) = self.focal else {
+            return self.base.get(dx.hypot(dy) / self.radius, pos);
+        };
+
+        // Keep the focal point inside the circle.
+        let (mut fx, mut fy) = (
+            (focal.x - self.center.x) as f32,
+            (focal.y - self.center.y) as f32,
+        );
+        let dist = fx.hypot(fy);
+        let max = self.radius * 0.99;
+        if dist > max {
+            (fx, fy) = (fx * max / dist, fy * max / dist);
+        }
+
+        // Find where the ray from the focal point through `pos` meets the circle.
+        let (px, py) = (dx - fx, dy - fy);
+        let len_sq = px * px + py * py;
+        if len_sq == 0. {
+            return self.base.get(0., pos);
+        }
+        let dot = fx * px + fy * py;
+        let c = fx * fx + fy * fy - self.radius * self.radius;
+        let s = (-dot + (dot * dot - len_sq * c).sqrt()) / len_sq;
+        self.base.get(1. / s, pos)
+    }
+    fn set_a(&self, a: u8) -> Self {
+        RadialGradient {
+            base: self.base.map(|c| c.set_a(a)),
+            ..self.clone()
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ConicGradient {
+    base: GradientBase,
+    center: Offset,
+    start_angle: f32,
+}
+
+impl ConicGradient {
+    /// Color values are sorted and clamped to 0-1, in turns counterclockwise around `center`, like arc angles.
+    /// Start angle in radians.
+    pub fn new(
+        colors: &[(f32, Rgba)],
+        interpolation: Interpolation,
+        center: Offset,
+        start_angle: f32,
+    ) -> Self {
+        Self {
+            base: GradientBase::from_stops(colors, false, interpolation),
+            center,
+            start_angle,
+        }
+    }
+    fn get(&self, pos: Offset) -> Rgba {
+        let angle = ((self.center.y - pos.y) as f32).atan2((pos.x - self.center.x) as f32);
+        let turns = (angle - self.start_angle).rem_euclid(TAU) / TAU;
+        self.base.get(turns, pos)
+    }
+    fn set_a(&self, a: u8) -> Self {
+        ConicGradient {
+            base: self.base.map(|c| c.set_a(a)),
+            ..self.clone()
+        }
+    }
+}
+
+/// How a [`Pattern`] fills the area outside its image.
+#[derive(Clone, Copy, Default)]
+pub enum Tiling {
+    #[default]
+    Repeat,
+    /// Repeats horizontally, transparent above and below.
+    RepeatX,
+    /// Repeats vertically, transparent left and right.
+    RepeatY,
+    /// Extends the edge pixels.
+    Clamp,
+    /// Repeats, flipping every other copy.
+    Mirror,
+}
+
+#[derive(Clone, Copy, Default)]
+pub enum Sampling {
+    #[default]
+    Nearest,
+    Bilinear,
+}
+
+/// Wraps a pixel coordinate into `0..len`, or `None` if transparent.
+fn wrap_coord(i: i32, len: i32, tiling: Option<Tiling>) -> Option<i32> {
+    match tiling {
+        None => (0..len).contains(&i).then_some(i),
+        Some(Tiling::Clamp) => Some(i.clamp(0, len - 1)),
+        Some(Tiling::Mirror) => {
+            let i = i.rem_euclid(2 * len);
+            Some(if i < len { i } else { 2 * len - 1 - i })
+        }
+        Some(_) => Some(i.rem_euclid(len)),
+    }
+}
+
+/// Fills with the pixels of a [`Buffer`].
+#[derive(Clone)]
+pub struct Pattern {
+    image: Buffer,
+    rect: Rect,
+    tiling: Tiling,
+    sampling: Sampling,
+    alpha: u8,
+}
+
+impl Pattern {
+    /// The image is stretched to fill `rect`, and tiled from there.
+    pub fn new(image: Buffer, rect: Rect, tiling: Tiling, sampling: Sampling) -> Self {
+        Self {
+            image,
+            rect,
+            tiling,
+            sampling,
+            alpha: 255,
+        }
+    }
+    fn texel(&self, x: i32, y: i32) -> Option<[u8; 3]> {
+        let (w, h) = (self.image.width as i32, self.image.height as i32);
+        let (tx, ty) = match self.tiling {
+            Tiling::RepeatX => (Some(Tiling::Repeat), None),
+            Tiling::RepeatY => (None, Some(Tiling::Repeat)),
+            t => (Some(t), Some(t)),
+        };
+        let x = wrap_coord(x, w, tx)? as usize;
+        let y = wrap_coord(y, h, ty)? as usize;
+        let i = (x + y * self.image.width) * 3;
+        let data = self.image.data.borrow();
+        Some([data[i], data[i + 1], data[i + 2]])
+    }
+    fn get(&self, pos: Offset) -> Rgba {
+        if self.image.width == 0 || self.image.height == 0 || self.rect.w == 0 || self.rect.h == 0 {
+            return Rgba::BLACK.set_a(0);
+        }
+        // Position of the pixel center in image pixels.
+        let u = (pos.x - self.rect.x) as f32 + 0.5;
+        let v = (pos.y - self.rect.y) as f32 + 0.5;
+        let u = u * self.image.width as f32 / self.rect.w as f32;
+        let v = v * self.image.height as f32 / self.rect.h as f32;
+        let rgba = match self.sampling {
+            Sampling::Nearest => match self.texel(u.floor() as i32, v.floor() as i32) {
+                Some(rgb) => rgb.into(),
+                None => Rgba::BLACK.set_a(0),
+            },
+            Sampling::Bilinear => {
+                let (u, v) = (u - 0.5, v - 0.5);
+                let (x, y) = (u.floor() as i32, v.floor() as i32);
+                let (fx, fy) = (u - x as f32, v - y as f32);
+                let mut sum = [0.; 4];
+                for (dx, dy, weight) in [
+                    (0, 0, (1. - fx) * (1. - fy)),
+                    (1, 0, fx * (1. - fy)),
+                    (0, 1, (1. - fx) * fy),
+                    (1, 1, fx * fy),
+                ] {
+                    if let Some([r, g, b]) = self.texel(x + dx, y + dy) {
+                        sum[0] += r as f32 * weight;
+                        sum[1] += g as f32 * weight;
+                        sum[2] += b as f32 * weight;
+                        sum[3] += weight;
+                    }
+                }
+                if sum[3] == 0. {
+                    Rgba::BLACK.set_a(0)
+                } else {
+                    // Transparent texels don't contribute color.
+                    Rgba {
+                        r: (sum[0] / sum[3]).round() as u8,
+                        g: (sum[1] / sum[3]).round() as u8,
+                        b: (sum[2] / sum[3]).round() as u8,
+                        a: (sum[3] * 255.).round() as u8,
+                    }
+                }
+            }
+        };
+        rgba.set_a(self.alpha)
+    }
+    fn set_a(&self, a: u8) -> Self {
+        Pattern {
+            alpha: (a as u16 * self.alpha as u16 / 255) as u8,
             ..self.clone()
         }
     }
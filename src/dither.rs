@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+
+use crate::{color::Rgba, Buffer};
+
+/// Threshold from a Bayer matrix of `2^bits` by `2^bits`, between 0 and 1.
+pub(crate) fn bayer(x: i32, y: i32, bits: u32) -> f32 {
+    let mut v = 0;
+    for bit in 0..bits {
+        let (xb, yb) = ((x >> bit) & 1, (y >> bit) & 1);
+        v = (v << 2) | ((xb ^ yb) << 1) | yb;
+    }
+    (v as f32 + 0.5) / (1 << (2 * bits)) as f32
+}
+
+#[derive(Clone, Copy, Default)]
+pub enum DitherMode {
+    /// Nearest palette color.
+    #[default]
+    None,
+    /// Ordered dithering with a 4x4 Bayer matrix.
+    Bayer4,
+    /// Ordered dithering with an 8x8 Bayer matrix.
+    Bayer8,
+    /// Error diffusion over 4 neighbors.
+    FloydSteinberg,
+    /// Error diffusion over 6 neighbors, dropping a quarter of the error for more contrast.
+    Atkinson,
+}
+
+/// Index of the palette color closest to `c`.
+pub(crate) fn nearest(palette: &[Rgba], c: [f32; 3]) -> usize {
+    let distance = |p: &Rgba| {
+        let (dr, dg, db) = (p.r as f32 - c[0], p.g as f32 - c[1], p.b as f32 - c[2]);
+        // Weighted for perceived brightness.
+        2. * dr * dr + 4. * dg * dg + 3. * db * db
+    };
+    (0..palette.len())
+        .min_by(|&a, &b| distance(&palette[a]).total_cmp(&distance(&palette[b])))
+        .unwrap_or(0)
+}
+
+/// How to choose a reduced palette for an image.
+#[derive(Clone, Copy, Default)]
+pub enum Quantizer {
+    /// Repeatedly splits the box of colors with the widest channel range at its median.
+    #[default]
+    MedianCut,
+    /// Merges the least distinct colors of an octree over the RGB bits.
+    Octree,
+}
+
+impl Quantizer {
+    /// Chooses up to `max_colors` colors from a histogram of colors to pixel counts.
+    pub(crate) fn palette(self, histogram: &HashMap<[u8; 3], u32>, max_colors: usize) -> Vec<Rgba> {
+        let max_colors = max_colors.max(1);
+        let mut colors: Vec<_> = histogram.iter().map(|(&c, &n)| (c, n)).collect();
+        // Sorted so the result doesn't depend on the hash order.
+        colors.sort_unstable();
+        if colors.len() <= max_colors {
+            return colors.into_iter().map(|(c, _)| Rgba::from(c)).collect();
+        }
+        match self {
+            Self::MedianCut => median_cut(colors, max_colors),
+            Self::Octree => octree(&colors, max_colors),
+        }
+    }
+}
+
+/// Average color of pixels, weighted by count.
+fn average(colors: impl Iterator<Item = ([u64; 3], u64)>) -> Rgba {
+    let (mut sum, mut total) = ([0u64; 3], 0);
+    for (c, n) in colors {
+        for (s, c) in sum.iter_mut().zip(c) {
+            *s += c;
+        }
+        total += n;
+    }
+    let total = total.max(1);
+    let c = |s: u64| ((s + total / 2) / total) as u8;
+    Rgba::from([c(sum[0]), c(sum[1]), c(sum[2])])
+}
+
+fn median_cut(colors: Vec<([u8; 3], u32)>, max_colors: usize) -> Vec<Rgba> {
+    // Widest channel and its range, weighted by the pixels in the box.
+    let widest = |colors: &[([u8; 3], u32)]| {
+        let pixels: u64 = colors.iter().map(|&(_, n)| n as u64).sum();
+        (0..3)
+            .map(|ch| {
+                let (min, max) = colors.iter().fold((255, 0), |(min, max), (c, _)| {
+                    (c[ch].min(min), c[ch].max(max))
+                });
+                (ch, (max - min) as u64 * pixels.isqrt())
+            })
+            .max_by_key(|&(_, range)| range)
+            .unwrap_or((0, 0))
+    };
+    let mut boxes = vec![colors];
+    while boxes.len() < max_colors {
+        let Some((i, ch)) = boxes
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| b.len() > 1)
+            .map(|(i, b)| (i, widest(b)))
+            .max_by_key(|&(_, (_, range))| range)
+            .map(|(i, (ch, _))| (i, ch))
+        else {
+            break;
+        };
+        let mut b = std::mem::take(&mut boxes[i]);
+        b.sort_unstable_by_key(|&(c, _)| c[ch]);
+        // Split at the pixel median, keeping at least one color on each side.
+        let half = b.iter().map(|&(_, n)| n as u64).sum::<u64>() / 2;
+        let mut seen = 0;
+        let at = b
+            .iter()
+            .position(|&(_, n)| {
+                seen += n as u64;
+                seen > half
+            })
+            .unwrap_or(0)
+            .clamp(1, b.len() - 1);
+        boxes[i] = b.split_off(at);
+        boxes.push(b);
+    }
+    boxes
+        .iter()
+        .map(|b| {
+            average(
+                b.iter()
+                    .map(|&(c, n)| (c.map(|v| v as u64 * n as u64), n as u64)),
+            )
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct OctreeNode {
+    children: [usize; 8],
+    sum: [u64; 3],
+    count: u64,
+}
+
+fn octree(colors: &[([u8; 3], u32)], max_colors: usize) -> Vec<Rgba> {
+    // Node 0 is the root, and 0 in `children` means no child.
+    let mut nodes = vec![OctreeNode::default()];
+    let mut levels: Vec<Vec<usize>> = vec![Vec::new(); 8];
+    for &(c, n) in colors {
+        let mut node = 0;
+        for level in 0..8 {
+            let shift = 7 - level;
+            let child = (((c[0] >> shift) & 1) << 2
+                | ((c[1] >> shift) & 1) << 1
+                | ((c[2] >> shift) & 1)) as usize;
+            if nodes[node].children[child] == 0 {
+                nodes.push(OctreeNode::default());
+                nodes[node].children[child] = nodes.len() - 1;
+                if level < 7 {
+                    levels[level + 1].push(nodes.len() - 1);
+                }
+            }
+            node = nodes[node].children[child];
+        }
+        let leaf = &mut nodes[node];
+        for (s, c) in leaf.sum.iter_mut().zip(c) {
+            *s += c as u64 * n as u64;
+        }
+        leaf.count += n as u64;
+    }
+    levels[0].push(0);
+
+    // Fold the deepest nodes into their parents, fewest pixels first, until few enough leaves remain.
+    let mut leaves = colors.len();
+    for level in (0..8).rev() {
+        let mut reducible = std::mem::take(&mut levels[level]);
+        reducible.sort_by_key(|&i| {
+            std::cmp::Reverse(
+                nodes[i]
+                    .children
+                    .iter()
+                    .filter(|&&c| c != 0)
+                    .map(|&c| nodes[c].count)
+                    .sum::<u64>(),
+            )
+        });
+        while leaves > max_colors {
+            let Some(i) = reducible.pop() else {
+                break;
+            };
+            let children = std::mem::take(&mut nodes[i].children);
+            for c in children.into_iter().filter(|&c| c != 0) {
+                let (sum, count) = (nodes[c].sum, std::mem::take(&mut nodes[c].count));
+                for (s, c) in nodes[i].sum.iter_mut().zip(sum) {
+                    *s += c;
+                }
+                nodes[i].count += count;
+                leaves -= 1;
+            }
+            leaves += 1;
+        }
+        if leaves <= max_colors {
+            break;
+        }
+    }
+    nodes
+        .iter()
+        .filter(|n| n.count > 0 && n.children.iter().all(|&c| c == 0))
+        .map(|n| average(std::iter::once((n.sum, n.count))))
+        .collect()
+}
+
+/// Maps RGB pixels to indices of the palette, dithering with `mode`.
+///
+/// Bayer offsets are scaled by the approximate spacing of the palette's colors.
+pub(crate) fn dither(data: &[u8], width: usize, palette: &[Rgba], mode: DitherMode) -> Vec<usize> {
+    let height = data.len() / 3 / width.max(1);
+    let mut indices = Vec::with_capacity(width * height);
+    let spread = 255. / ((palette.len() as f32).cbrt() - 1.).max(1.);
+    // Error carried to the next rows, with padding for the edges.
+    let mut errors = vec![[0f32; 3]; (width + 4) * 3];
+    let diffusion: &[(isize, usize, f32)] = match mode {
+        DitherMode::FloydSteinberg => &[
+            (1, 0, 7. / 16.),
+            (-1, 1, 3. / 16.),
+            (0, 1, 5. / 16.),
+            (1, 1, 1. / 16.),
+        ],
+        DitherMode::Atkinson => &[
+            (1, 0, 1. / 8.),
+            (2, 0, 1. / 8.),
+            (-1, 1, 1. / 8.),
+            (0, 1, 1. / 8.),
+            (1, 1, 1. / 8.),
+            (0, 2, 1. / 8.),
+        ],
+        _ => &[],
+    };
+    for y in 0..height {
+        for x in 0..width {
+            let i = (x + y * width) * 3;
+            let mut c = [data[i] as f32, data[i + 1] as f32, data[i + 2] as f32];
+            let offset = match mode {
+                DitherMode::Bayer4 => (bayer(x as i32, y as i32, 2) - 0.5) * spread,
+                DitherMode::Bayer8 => (bayer(x as i32, y as i32, 3) - 0.5) * spread,
+                _ => 0.,
+            };
+            let error = &mut errors[(y % 3) * (width + 4) + x + 2];
+            for (c, e) in c.iter_mut().zip(error) {
+                *c += *e + offset;
+                *e = 0.;
+            }
+            let index = nearest(palette, c);
+            indices.push(index);
+
+            let p = palette[index];
+            let error = [c[0] - p.r as f32, c[1] - p.g as f32, c[2] - p.b as f32];
+            for &(dx, dy, weight) in diffusion {
+                let row = (y + dy) % 3;
+                let e = &mut errors[row * (width + 4) + (x as isize + 2 + dx) as usize];
+                for (e, error) in e.iter_mut().zip(error) {
+                    *e += error * weight;
+                }
+            }
+        }
+    }
+    indices
+}
+
+impl Buffer {
+    /// Chooses a palette of up to `max_colors` colors for the buffer, to use with [`Buffer::quantize`].
+    pub fn palette(&self, max_colors: usize, quantizer: Quantizer) -> Vec<Rgba> {
+        let mut histogram = HashMap::new();
+        for c in self.data.borrow().chunks_exact(3) {
+            *histogram.entry([c[0], c[1], c[2]]).or_insert(0) += 1;
+        }
+        quantizer.palette(&histogram, max_colors)
+    }
+
+    /// Reduces the buffer to colors of the palette. Alpha of the palette is ignored.
+    ///
+    /// Bayer offsets are scaled by the approximate spacing of the palette's colors.
+    pub fn quantize(&self, palette: &[Rgba], mode: DitherMode) {
+        if palette.is_empty() {
+            return;
+        }
+        let mut data = self.data.borrow_mut();
+        let indices = dither(&data, self.width, palette, mode);
+        for (c, i) in data.chunks_exact_mut(3).zip(indices) {
+            let p = palette[i];
+            c.copy_from_slice(&[p.r, p.g, p.b]);
+        }
+    }
+}
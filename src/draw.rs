@@ -1,9 +1,13 @@
 use core::f32::consts::{FRAC_PI_2 as PI_2_32, PI as PI32, TAU as TAU32};
 use std::{cell::RefCell, mem, rc::Rc};
 
-use crate::{Offset, Rect, Size};
+use crate::{CornerRadii, Offset, Path, Radius, Rect, Size, StrokeStyle};
 
-use crate::color::Color;
+use crate::blend::{BlendMode, CompositeOp};
+use crate::color::{Color, Rgba};
+use crate::text::{
+    break_lines, ellipsize, space, width, BuiltinFont, Font, TextAlign, TextStyle, VerticalAlign,
+};
 
 macro_rules! quadrant {
     ($($fn:ident).+($cx:expr,$cy:expr,$x:expr,$y:expr,$color:expr)) => {
@@ -20,6 +24,367 @@ macro_rules! quadrant {
     };
 }
 
+/// Rule deciding which parts of a (possibly self-intersecting) outline are inside.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum FillRule {
+    /// Inside if a ray from the point crosses the outline an odd number of times.
+    EvenOdd,
+    /// Inside if the outline winds around the point a non-zero number of times.
+    #[default]
+    NonZero,
+}
+
+/// Vertical samples per pixel row used by antialiased scanline fills.
+const SUBSAMPLES: usize = 5;
+
+struct Edge {
+    x0: f32,
+    y0: f32,
+    x1: f32,
+    y1: f32,
+    dir: i32,
+}
+
+/// Adds `weight` times the covered part of `xa..xb` to each pixel of a scanline. Returns the touched range.
+fn add_span(coverage: &mut [f32], xa: f32, xb: f32, weight: f32) -> Option<(usize, usize)> {
+    let width = (coverage.len() - 1) as f32;
+    let (xa, xb) = (xa.max(0.), xb.min(width));
+    if xa >= xb {
+        return None;
+    }
+    let (ia, ib) = (xa as usize, xb as usize);
+    if ia == ib {
+        coverage[ia] += (xb - xa) * weight;
+    } else {
+        coverage[ia] += (ia as f32 + 1. - xa) * weight;
+        for c in &mut coverage[ia + 1..ib] {
+            *c += weight;
+        }
+        coverage[ib] += (xb - ib as f32) * weight;
+    }
+    Some((ia, ib + 1))
+}
+
+/// Scanline fill of closed contours, with coordinates on pixel corners.
+///
+/// Calls `plot` for every pixel inside `0..clip.w`, `0..clip.h` that is at least partially covered, along with its coverage.
+/// Without antialiasing, a pixel is covered if its center is inside.
+pub(crate) fn rasterize<F: FnMut(i32, i32, u8)>(
+    contours: &[Vec<(f32, f32)>],
+    rule: FillRule,
+    aa: bool,
+    clip: Size,
+    mut plot: F,
+) {
+    let mut edges = Vec::new();
+    for contour in contours {
+        for (i, &(x0, y0)) in contour.iter().enumerate() {
+            let (x1, y1) = contour[(i + 1) % contour.len()];
+            if y0 == y1 || !(x0.is_finite() && y0.is_finite() && x1.is_finite() && y1.is_finite()) {
+                continue;
+            }
+            if y0 < y1 {
+                edges.push(Edge {
+                    x0,
+                    y0,
+                    x1,
+                    y1,
+                    dir: 1,
+                });
+            } else {
+                edges.push(Edge {
+                    x0: x1,
+                    y0: y1,
+                    x1: x0,
+                    y1: y0,
+                    dir: -1,
+                });
+            }
+        }
+    }
+    if edges.is_empty() || clip.w == 0 || clip.h == 0 {
+        return;
+    }
+    edges.sort_by(|a, b| a.y0.total_cmp(&b.y0));
+
+    let y_min = (edges[0].y0.floor().max(0.)) as i32;
+    let y_max = edges.iter().map(|e| e.y1).fold(f32::MIN, f32::max).ceil();
+    let y_max = y_max.min(clip.h as f32) as i32;
+
+    let samples = if aa { SUBSAMPLES } else { 1 };
+    let weight = 1. / samples as f32;
+    // One extra cell, so spans ending exactly on the right border don't need a bounds check.
+    let mut coverage = vec![0f32; clip.w as usize + 1];
+    let mut crossings: Vec<(f32, i32)> = Vec::new();
+    let mut active: Vec<usize> = Vec::new();
+    let mut next = 0;
+    for y in y_min..y_max {
+        while next < edges.len() && edges[next].y0 < (y + 1) as f32 {
+            active.push(next);
+            next += 1;
+        }
+        active.retain(|&i| edges[i].y1 > y as f32);
+
+        let (mut lo, mut hi) = (usize::MAX, 0);
+        for s in 0..samples {
+            let sy = y as f32 + (s as f32 + 0.5) * weight;
+            crossings.clear();
+            for &i in &active {
+                let e = &edges[i];
+                if e.y0 <= sy && sy < e.y1 {
+                    let x = e.x0 + (sy - e.y0) * (e.x1 - e.x0) / (e.y1 - e.y0);
+                    crossings.push((x, e.dir));
+                }
+            }
+            crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+            let mut winding = 0;
+            for pair in crossings.windows(2) {
+                winding += pair[0].1;
+                let inside = match rule {
+                    FillRule::EvenOdd => winding % 2 != 0,
+                    FillRule::NonZero => winding != 0,
+                };
+                if !inside {
+                    continue;
+                }
+                let (xa, xb) = if aa {
+                    (pair[0].0, pair[1].0)
+                } else {
+                    // Snap to the first pixel center inside and the first one outside.
+                    ((pair[0].0 - 0.5).ceil(), (pair[1].0 - 0.5).ceil())
+                };
+                if let Some((a, b)) = add_span(&mut coverage, xa, xb, weight) {
+                    lo = lo.min(a);
+                    hi = hi.max(b);
+                }
+            }
+        }
+
+        if lo >= hi {
+            continue;
+        }
+        for (x, cov) in coverage[lo..hi].iter_mut().enumerate() {
+            let c = (*cov * 255. + 0.5).min(255.) as u8;
+            *cov = 0.;
+            if c > 0 && lo + x < clip.w as usize {
+                plot((lo + x) as i32, y, c);
+            }
+        }
+    }
+}
+
+/// Draws a pixel with `color`, scaling its alpha by the coverage `c`.
+fn plot_coverage<D: Drawable + ?Sized>(this: &D, x: i32, y: i32, c: u8, color: &Color) {
+    if c == 255 {
+        this.point(x, y, color);
+    } else {
+        this.point(x, y, &color.set_a(c));
+    }
+}
+
+/// Draws a single line of text, with the pen starting on the baseline at `pen`. Pixels outside `clip` are skipped.
+fn text_run<D: Drawable + ?Sized>(
+    this: &D,
+    mut pen: Offset,
+    text: &str,
+    font: &dyn Font,
+    clip: Option<Rect>,
+    color: &Color,
+) {
+    let mut prev = None;
+    for c in text.chars() {
+        if let Some(p) = prev {
+            pen.x += font.kerning(p, c);
+        }
+        let glyph = font.glyph(c);
+        let origin = pen + glyph.offset;
+        for (i, &c) in glyph.coverage.iter().enumerate() {
+            let x = origin.x + (i as u32 % glyph.size.w) as i32;
+            let y = origin.y + (i as u32 / glyph.size.w) as i32;
+            let inside = clip.is_none_or(|r| {
+                x >= r.x && y >= r.y && x < r.x + r.w as i32 && y < r.y + r.h as i32
+            });
+            if c != 0 && inside {
+                plot_coverage(this, x, y, c, color);
+            }
+        }
+        pen.x += glyph.advance;
+        prev = Some(c);
+    }
+}
+
+/// Calls `f` with the points of an aliased ellipse outline in the quadrant with positive `x` and `y`.
+fn ellipse_quadrant<F: FnMut(i32, i32)>(rx: i32, ry: i32, mut f: F) {
+    if rx == 0 || ry == 0 {
+        for x in 0..=rx {
+            f(x, 0);
+        }
+        for y in 1..=ry {
+            f(0, y);
+        }
+        return;
+    }
+    // Midpoint ellipse algorithm, walking x where the slope is shallow, then y.
+    let (sqx, sqy) = ((rx as i64).pow(2), (ry as i64).pow(2));
+    let (mut x, mut y) = (0i64, ry as i64);
+    let (mut dx, mut dy) = (0, 2 * sqx * y);
+    let mut d = sqy as f64 - (sqx * ry as i64) as f64 + sqx as f64 / 4.;
+    while dx < dy {
+        f(x as i32, y as i32);
+        x += 1;
+        dx += 2 * sqy;
+        if d < 0. {
+            d += (sqy + dx) as f64;
+        } else {
+            y -= 1;
+            dy -= 2 * sqx;
+            d += (sqy + dx - dy) as f64;
+        }
+    }
+    let mut d =
+        sqy as f64 * (x as f64 + 0.5).powi(2) + (sqx * (y - 1) * (y - 1) - sqx * sqy) as f64;
+    while y >= 0 {
+        f(x as i32, y as i32);
+        y -= 1;
+        dy -= 2 * sqx;
+        if d > 0. {
+            d += (sqx - dy) as f64;
+        } else {
+            x += 1;
+            dx += 2 * sqy;
+            d += (sqx - dy + dx) as f64;
+        }
+    }
+}
+
+/// Calls `f` with the points of an aliased circle outline in the quadrant with positive `x` and `y`, like [`Drawable::circle`].
+fn circle_quadrant<F: FnMut(i32, i32)>(r: i32, mut f: F) {
+    let mut e = (1 - r) / 2;
+    let (mut x, mut y) = (r, 0);
+    while x >= y {
+        f(x, y);
+        if x != y {
+            f(y, x);
+        }
+        y += 1;
+        if e >= 0 {
+            x -= 1;
+            e -= x;
+        }
+        e += y;
+    }
+}
+
+/// Coverage of the pixel `(x, y)` from the center of a circle outline of radius `r`, like [`Drawable::circle_aa`].
+fn circle_coverage(x: i32, y: i32, r: i32) -> u8 {
+    let (r, sqd) = (r as i64, (x as i64).pow(2) + (y as i64).pow(2));
+    let (rmin, rmax) = (r * (r - 2), r * (r + 2));
+    let c = if sqd < rmax && sqd >= r * r {
+        rmax - sqd
+    } else if sqd < r * r && sqd >= rmin {
+        sqd - rmin
+    } else {
+        return 0;
+    };
+    (c * 256 / (2 * r)).min(255) as u8
+}
+
+/// Coverage of the pixel `(x, y)` from the center of a filled circle of radius `r`, like [`Drawable::fill_circle_aa`].
+fn fill_circle_coverage(x: i32, y: i32, r: i32) -> u8 {
+    let (r, sqd) = (r as i64, (x as i64).pow(2) + (y as i64).pow(2));
+    let (rmin, rmax) = (if r == 0 { 1 } else { r * r }, r * (r + 2));
+    if sqd < rmin {
+        255
+    } else if sqd < rmax {
+        ((rmax - sqd) * 256 / (2 * r)).min(255) as u8
+    } else {
+        0
+    }
+}
+
+/// Approximate signed distance from `(x, y)` to an ellipse centered on the origin, negative inside.
+fn ellipse_distance(x: f32, y: f32, rx: f32, ry: f32) -> f32 {
+    let (rx, ry) = (rx.max(0.5), ry.max(0.5));
+    let f = (x / rx).powi(2) + (y / ry).powi(2) - 1.;
+    let grad = 2. * ((x / (rx * rx)).powi(2) + (y / (ry * ry)).powi(2)).sqrt();
+    if grad == 0. {
+        return -rx.min(ry);
+    }
+    f / grad
+}
+
+/// Whether the polar angle of a pixel offset (with `y` pointing down) is in `angle1..=angle2`, with `0 <= angle1 <= angle2 <= TAU`.
+fn in_angle_range(x: i32, y: i32, angle1: f32, angle2: f32) -> bool {
+    let angle = (-y as f32).atan2(x as f32).rem_euclid(TAU32);
+    (angle1 <= angle && angle <= angle2) || angle + TAU32 <= angle2
+}
+
+/// Centers of the corners of a rounded rectangle spanning pixels `p1` to `p3`, with radii and outwards directions.
+fn corners(p1: Offset, p3: Offset, radii: &CornerRadii) -> [(Offset, Radius, i32, i32); 4] {
+    let (tl, tr, br, bl) = (radii.tl, radii.tr, radii.br, radii.bl);
+    [
+        (
+            Offset::new(p1.x + tl.x as i32, p1.y + tl.y as i32),
+            tl,
+            -1,
+            -1,
+        ),
+        (
+            Offset::new(p3.x - tr.x as i32, p1.y + tr.y as i32),
+            tr,
+            1,
+            -1,
+        ),
+        (
+            Offset::new(p3.x - br.x as i32, p3.y - br.y as i32),
+            br,
+            1,
+            1,
+        ),
+        (
+            Offset::new(p1.x + bl.x as i32, p3.y - bl.y as i32),
+            bl,
+            -1,
+            1,
+        ),
+    ]
+}
+
+/// Finds the corner from [`corners`] containing a pixel, returning the offset from the corner's center pointing outwards, and its radius.
+fn corner_offset(
+    corners: &[(Offset, Radius, i32, i32); 4],
+    x: i32,
+    y: i32,
+) -> Option<(i32, i32, Radius)> {
+    corners
+        .iter()
+        .map(|&(c, r, sx, sy)| (sx * (x - c.x), sy * (y - c.y), r))
+        .find(|&(x, y, _)| x >= 0 && y >= 0)
+}
+
+/// Draws the straight edges of a rounded rectangle border spanning pixels `p1` to `p3`.
+fn round_rect_edges<D: Drawable + ?Sized>(
+    this: &D,
+    p1: Offset,
+    p3: Offset,
+    radii: &CornerRadii,
+    color: &Color,
+) {
+    for x in p1.x + radii.tl.x as i32 + 1..p3.x - radii.tr.x as i32 {
+        this.point(x, p1.y, color);
+    }
+    for x in p1.x + radii.bl.x as i32 + 1..p3.x - radii.br.x as i32 {
+        this.point(x, p3.y, color);
+    }
+    for y in p1.y + radii.tl.y as i32 + 1..p3.y - radii.bl.y as i32 {
+        this.point(p1.x, y, color);
+    }
+    for y in p1.y + radii.tr.y as i32 + 1..p3.y - radii.br.y as i32 {
+        this.point(p3.x, y, color);
+    }
+}
+
 pub trait Drawable {
     /// Get the buffer's size
     fn size(&self) -> Size;
@@ -32,6 +397,13 @@ pub trait Drawable {
 
     fn get_subregion(&self) -> Rect;
 
+    /// Set how drawn colors mix with the existing pixels. Targets without blend modes ignore it.
+    fn set_blend_mode(&mut self, _mode: BlendMode) {}
+
+    fn blend_mode(&self) -> BlendMode {
+        BlendMode::Normal
+    }
+
     /// Draw one pixel.
     fn point(&self, x: i32, y: i32, color: &Color);
 
@@ -538,208 +910,472 @@ pub trait Drawable {
         }
     }
 
-    /// Draws an aliased rounded rectangle border.
-    fn round_rect(&self, rect: Rect, radius: u32, color: Color) {
+    /// Draws an aliased rounded rectangle border. Radii are clamped like [`CornerRadii::clamp`], and circular corners are drawn like [`Self::circle`].
+    fn round_rect(&self, rect: Rect, radii: impl Into<CornerRadii>, color: Color)
+    where
+        Self: Sized,
+    {
         let p1 = rect.offset();
         let p3 = rect.offset_2() - Offset::new(1, 1);
+        let radii = radii.into().clamp(p3.abs_diff(p1));
+        round_rect_edges(self, p1, p3, &radii, &color);
 
-        let p1_c = Offset {
-            x: p1.x + radius as i32,
-            y: p1.y + radius as i32,
-        };
-        let p3_c = Offset {
-            x: p3.x - radius as i32,
-            y: p3.y - radius as i32,
-        };
-
-        for x in p1_c.x + 1..p3_c.x {
-            self.point(x, p1.y, &color);
-            self.point(x, p3.y, &color);
-        }
-        for y in p1_c.y + 1..p3_c.y {
-            self.point(p1.x, y, &color);
-            self.point(p3.x, y, &color);
+        for (c, r, sx, sy) in corners(p1, p3, &radii) {
+            let plot = |x, y| self.point(c.x + sx * x, c.y + sy * y, &color);
+            if r.x == r.y {
+                circle_quadrant(r.x as i32, plot);
+            } else {
+                ellipse_quadrant(r.x as i32, r.y as i32, plot);
+            }
         }
+    }
 
-        let mut e = (1 - radius as i32) / 2;
-        let mut x = radius as i32;
-        let mut y = 0;
-        while x >= y {
-            if x != y {
-                self.point(p3_c.x + y, p3_c.y + x, &color);
-                self.point(p1_c.x - y, p3_c.y + x, &color);
-                self.point(p3_c.x + y, p1_c.y - x, &color);
-                self.point(p1_c.x - y, p1_c.y - x, &color);
-            }
-            self.point(p3_c.x + x, p3_c.y + y, &color);
-            self.point(p1_c.x - x, p3_c.y + y, &color);
-            self.point(p3_c.x + x, p1_c.y - y, &color);
-            self.point(p1_c.x - x, p1_c.y - y, &color);
-            y += 1;
-            if e >= 0 {
-                x -= 1;
-                e -= x;
+    /// Draws an antialiased rounded rectangle border. Radii are clamped like [`CornerRadii::clamp`], and circular corners are drawn like [`Self::circle_aa`].
+    fn round_rect_aa(&self, rect: Rect, radii: impl Into<CornerRadii>, color: Color)
+    where
+        Self: Sized,
+    {
+        let p1 = rect.offset();
+        let p3 = rect.offset_2() - Offset::new(1, 1);
+        let radii = radii.into().clamp(p3.abs_diff(p1));
+        round_rect_edges(self, p1, p3, &radii, &color);
+
+        for (c, r, sx, sy) in corners(p1, p3, &radii) {
+            let (rx, ry) = (r.x as i32, r.y as i32);
+            for y in 0..=ry {
+                for x in 0..=rx {
+                    let c_a = if rx == 0 || ry == 0 {
+                        // Square corner, continuing the straight edges.
+                        if x == rx || y == ry {
+                            255
+                        } else {
+                            0
+                        }
+                    } else if rx == ry {
+                        circle_coverage(x, y, rx)
+                    } else {
+                        let d = ellipse_distance(x as f32, y as f32, r.x as f32, r.y as f32);
+                        ((1. - d.abs()).max(0.) * 255.) as u8
+                    };
+                    if c_a > 0 {
+                        plot_coverage(self, c.x + sx * x, c.y + sy * y, c_a, &color);
+                    }
+                }
             }
-            e += y;
         }
     }
 
-    /// Draws an antialiased rounded rectangle border.
-    fn round_rect_aa(&self, rect: Rect, radius: u32, color: Color) {
-        let rmin = (radius * (radius - 2)) as i32;
-        let rmax = if radius == 0 {
-            1
-        } else {
-            radius * (radius + 2)
-        } as i32;
-
+    /// Draws a filled aliased rounded rectangle. Radii are clamped like [`CornerRadii::clamp`].
+    fn fill_round_rect(&self, rect: Rect, radii: impl Into<CornerRadii>, color: Color)
+    where
+        Self: Sized,
+    {
         let p1 = rect.offset();
         let p3 = rect.offset_2() - Offset::new(1, 1);
+        let radii = radii.into().clamp(p3.abs_diff(p1));
+        let corners = corners(p1, p3, &radii);
 
-        let p1_c = Offset {
-            x: p1.x + radius as i32,
-            y: p1.y + radius as i32,
-        };
-        let p3_c = Offset {
-            x: p3.x - radius as i32,
-            y: p3.y - radius as i32,
-        };
-
-        for x in p1_c.x + 1..p3_c.x {
-            self.point(x, p1.y, &color);
-            self.point(x, p3.y, &color);
-        }
-        for y in p1_c.y + 1..p3_c.y {
-            self.point(p1.x, y, &color);
-            self.point(p3.x, y, &color);
-        }
-
-        for y in 0..=radius as i32 {
-            let sqy = y * y;
-            for x in 0..=radius as i32 {
-                let sqd = x * x + sqy;
-                if sqd < rmax && sqd >= (radius * radius) as i32 {
-                    let mut c = rmax - sqd;
-                    c *= 256;
-                    c /= 2 * radius as i32;
-                    if c > 255 {
-                        c = 255
-                    };
-                    self.point(p1_c.x - x, p1_c.y - y, &color.set_a(c as u8));
-                    self.point(p3_c.x + x, p1_c.y - y, &color.set_a(c as u8));
-                    self.point(p1_c.x - x, p3_c.y + y, &color.set_a(c as u8));
-                    self.point(p3_c.x + x, p3_c.y + y, &color.set_a(c as u8));
-                } else if sqd < (radius * radius) as i32 && sqd >= rmin {
-                    let mut c = sqd - rmin;
-                    c *= 256;
-                    c /= 2 * radius as i32;
-                    if c > 255 {
-                        c = 255
-                    };
-                    self.point(p1_c.x - x, p1_c.y - y, &color.set_a(c as u8));
-                    self.point(p3_c.x + x, p1_c.y - y, &color.set_a(c as u8));
-                    self.point(p1_c.x - x, p3_c.y + y, &color.set_a(c as u8));
-                    self.point(p3_c.x + x, p3_c.y + y, &color.set_a(c as u8));
+        for y in p1.y..=p3.y {
+            for x in p1.x..=p3.x {
+                let inside = match corner_offset(&corners, x, y) {
+                    Some((x, y, r)) if r.x == r.y => {
+                        let (x, y, r) = (x as i64, y as i64, r.x as i64);
+                        x * x + y * y <= r * (r + 2)
+                    }
+                    Some((x, y, r)) => {
+                        let sqx = (r.x as f32 * (r.x as f32 + 2.)).max(1.);
+                        let sqy = (r.y as f32 * (r.y as f32 + 2.)).max(1.);
+                        (x as f32).powi(2) / sqx + (y as f32).powi(2) / sqy <= 1.
+                    }
+                    None => true,
+                };
+                if inside {
+                    self.point(x, y, &color);
                 }
             }
         }
     }
 
-    /// Draws a filled aliased rounded rectangle.
-    fn fill_round_rect(&self, rect: Rect, radius: u32, color: Color) {
+    /// Draws a filled antialiased rounded rectangle. Radii are clamped like [`CornerRadii::clamp`], and circular corners are drawn like [`Self::fill_circle_aa`].
+    fn fill_round_rect_aa(&self, rect: Rect, radii: impl Into<CornerRadii>, color: Color)
+    where
+        Self: Sized,
+    {
         let p1 = rect.offset();
         let p3 = rect.offset_2() - Offset::new(1, 1);
+        let radii = radii.into().clamp(p3.abs_diff(p1));
+        let corners = corners(p1, p3, &radii);
 
-        let p1_c = Offset {
-            x: p1.x + radius as i32,
-            y: p1.y + radius as i32,
-        };
-        let p3_c = Offset {
-            x: p3.x - radius as i32,
-            y: p3.y - radius as i32,
-        };
-
-        for y in p1_c.y + 1..p3_c.y {
+        for y in p1.y..=p3.y {
             for x in p1.x..=p3.x {
-                self.point(x, y, &color);
+                let c = match corner_offset(&corners, x, y) {
+                    Some((x, y, r)) if r.x == r.y => fill_circle_coverage(x, y, r.x as i32),
+                    Some((x, y, r)) => {
+                        let d = ellipse_distance(x as f32, y as f32, r.x as f32, r.y as f32);
+                        ((1. - d).clamp(0., 1.) * 255.) as u8
+                    }
+                    None => 255,
+                };
+                if c > 0 {
+                    plot_coverage(self, x, y, c, &color);
+                }
             }
         }
-        for x in p1_c.x + 1..p3_c.x {
-            for y in p1.y..=p1_c.y {
-                self.point(x, y, &color);
-            }
-            for y in p3_c.y..=p3.y {
-                self.point(x, y, &color);
+    }
+
+    /// Draws an aliased ellipse.
+    fn ellipse(&self, center: Offset, radius: Radius, color: Color) {
+        ellipse_quadrant(radius.x as i32, radius.y as i32, |x, y| {
+            quadrant!(self.point(center.x, center.y, x, y, &color));
+        });
+    }
+
+    /// Draws an antialiased ellipse.
+    fn ellipse_aa(&self, center: Offset, radius: Radius, color: Color) {
+        for y in 0..=radius.y as i32 + 1 {
+            for x in 0..=radius.x as i32 + 1 {
+                let d = ellipse_distance(x as f32, y as f32, radius.x as f32, radius.y as f32);
+                let c = 1. - d.abs();
+                if c > 0. {
+                    quadrant!(self.point(center.x, center.y, x, y, &color.set_a((c * 255.) as u8)));
+                }
             }
         }
+    }
 
-        for y in 0..=radius as i32 {
-            let sqy = y * y;
-            for x in 0..=radius as i32 {
-                let sqd = x * x + sqy;
-                if sqd <= (radius * (2 + radius)) as i32 {
-                    self.point(p1_c.x - x, p1_c.y - y, &color);
-                    self.point(p3_c.x + x, p1_c.y - y, &color);
-                    self.point(p1_c.x - x, p3_c.y + y, &color);
-                    self.point(p3_c.x + x, p3_c.y + y, &color);
+    /// Draws an aliased, filled ellipse.
+    fn fill_ellipse(&self, center: Offset, radius: Radius, color: Color) {
+        let (rx, ry) = (radius.x as i32, radius.y as i32);
+        // Same as `x^2 + y^2 <= r * (r + 1)` on circles.
+        let sq = |r: i32| (r as i64 * (r as i64 + 1)).max(1) as f32;
+        let (sqx, sqy) = (sq(rx), sq(ry));
+        for y in 0..=ry {
+            for x in 0..=rx {
+                if (x as i64).pow(2) as f32 / sqx + (y as i64).pow(2) as f32 / sqy <= 1. {
+                    quadrant!(self.point(center.x, center.y, x, y, &color));
                 }
             }
         }
     }
 
-    /// Draws a filled antialiased rounded rectangle.
-    fn fill_round_rect_aa(&self, rect: Rect, radius: u32, color: Color) {
-        let rmin = if radius == 0 { 1 } else { radius * radius } as i32;
-        let rmax = (radius * (radius + 2)) as i32;
+    /// Draws an antialiased, filled ellipse.
+    fn fill_ellipse_aa(&self, center: Offset, radius: Radius, color: Color) {
+        for y in 0..=radius.y as i32 + 1 {
+            for x in 0..=radius.x as i32 + 1 {
+                let d = ellipse_distance(x as f32, y as f32, radius.x as f32, radius.y as f32);
+                if d <= 0. {
+                    quadrant!(self.point(center.x, center.y, x, y, &color));
+                } else if d < 1. {
+                    let c = ((1. - d) * 255.) as u8;
+                    quadrant!(self.point(center.x, center.y, x, y, &color.set_a(c)));
+                }
+            }
+        }
+    }
 
-        let p1 = rect.offset();
-        let p3 = rect.offset_2() - Offset::new(1, 1);
+    /// Draws an aliased ellipse arc from angle1 to angle2 in radians, with positive angles measured counterclockwise from positive x axis.
+    ///
+    /// Angles are polar, so the arc ends on the rays from the center at those angles.
+    fn ellipse_arc(&self, center: Offset, radius: Radius, angle1: f32, angle2: f32, color: Color) {
+        let (angle1, angle2) = if angle2 < angle1 % TAU32 {
+            // Corner case where the arc overlaps angle 0.
+            self.ellipse_arc(center, radius, angle1, TAU32, color.clone());
+            self.ellipse_arc(center, radius, 0., angle2, color);
+            return;
+        } else if angle2 - angle1 >= TAU32 {
+            (0., TAU32)
+        } else {
+            // The subtractions make zero and TAU become TAU, not zero.
+            (angle1 % TAU32, TAU32 - (TAU32 - angle2) % TAU32)
+        };
 
-        let p1_c = Offset {
-            x: p1.x + radius as i32,
-            y: p1.y + radius as i32,
+        let plot = |x: i32, y: i32, color: &Color| {
+            if in_angle_range(x - center.x, y - center.y, angle1, angle2) {
+                self.point(x, y, color);
+            }
         };
-        let p3_c = Offset {
-            x: p3.x - radius as i32,
-            y: p3.y - radius as i32,
+        ellipse_quadrant(radius.x as i32, radius.y as i32, |x, y| {
+            quadrant!(plot(center.x, center.y, x, y, &color));
+        });
+    }
+
+    /// Draws an aliased, filled ellipse pie, with angles like [`Self::ellipse_arc`].
+    fn ellipse_pie(&self, center: Offset, radius: Radius, angle1: f32, angle2: f32, color: Color) {
+        let (angle1, angle2) = if angle2 < angle1 % TAU32 {
+            // Corner case where the arc overlaps angle 0.
+            self.ellipse_pie(center, radius, angle1, TAU32, color.clone());
+            self.ellipse_pie(center, radius, 0., angle2, color);
+            return;
+        } else if angle2 - angle1 >= TAU32 {
+            (0., TAU32)
+        } else {
+            // The subtractions make zero and TAU become TAU, not zero.
+            (angle1 % TAU32, TAU32 - (TAU32 - angle2) % TAU32)
         };
 
-        for y in p1_c.y + 1..p3_c.y {
-            for x in p1.x..=p3.x {
-                self.point(x, y, &color);
+        let (rx, ry) = (radius.x as i32, radius.y as i32);
+        let sq = |r: i32| (r as i64 * (r as i64 + 1)).max(1) as f32;
+        let (sqx, sqy) = (sq(rx), sq(ry));
+        let plot = |x: i32, y: i32, color: &Color| {
+            if in_angle_range(x - center.x, y - center.y, angle1, angle2) {
+                self.point(x, y, color);
+            }
+        };
+        for y in 0..=ry {
+            for x in 0..=rx {
+                if (x as i64).pow(2) as f32 / sqx + (y as i64).pow(2) as f32 / sqy <= 1. {
+                    quadrant!(plot(center.x, center.y, x, y, &color));
+                }
             }
         }
-        for x in p1_c.x + 1..p3_c.x {
-            for y in p1.y..=p1_c.y {
-                self.point(x, y, &color);
+    }
+
+    /// Draws text with the [`BuiltinFont`], with `pos` at the top left of the first line. Lines are separated by `\n`.
+    fn text(&self, pos: Offset, text: &str, color: Color) {
+        self.text_font(pos, text, &BuiltinFont, color);
+    }
+
+    /// Draws text with the given font, with `pos` at the top left of the first line. Lines are separated by `\n`.
+    fn text_font(&self, pos: Offset, text: &str, font: &dyn Font, color: Color) {
+        let mut pen = Offset::new(pos.x, pos.y + font.ascent());
+        for line in text.split('\n') {
+            text_run(self, pen, line, font, None, &color);
+            pen.y += font.line_height();
+        }
+    }
+
+    /// Lays out text inside `rect`, wrapping, aligning and truncating it as set by `style`. Text is clipped to `rect`.
+    fn text_in_rect(&self, rect: Rect, text: &str, style: TextStyle) {
+        let font = style.font;
+        let max_width = rect.w as i32;
+        let mut lines = break_lines(text, font, max_width, style.wrap);
+        let line_height = (font.line_height() as f32 * style.line_height).round() as i32;
+        let text_height = font.ascent() + font.descent();
+        if style.ellipsis {
+            let fit = if line_height > 0 {
+                ((rect.h as i32 - text_height) / line_height + 1).max(1) as usize
+            } else {
+                lines.len()
+            };
+            if lines.len() > fit {
+                lines.truncate(fit);
+                ellipsize(lines.last_mut().unwrap(), font, max_width);
             }
-            for y in p3_c.y..=p3.y {
-                self.point(x, y, &color);
+            for line in lines.iter_mut().filter(|line| line.width > max_width) {
+                ellipsize(line, font, max_width);
             }
         }
-        for y in 0..=radius as i32 {
-            let sqy = y * y;
-            for x in 0..=radius as i32 {
-                let sqd = x * x + sqy;
-                if sqd < rmin {
-                    self.point(p1_c.x - x, p1_c.y - y, &color);
-                    self.point(p3_c.x + x, p1_c.y - y, &color);
-                    self.point(p1_c.x - x, p3_c.y + y, &color);
-                    self.point(p3_c.x + x, p3_c.y + y, &color);
-                } else if sqd < rmax {
-                    let mut c = rmax - sqd;
-                    c *= 256;
-                    c /= 2 * radius as i32;
-                    if c > 255 {
-                        c = 255
-                    };
-                    self.point(p1_c.x - x, p1_c.y - y, &color.set_a(c as u8));
-                    self.point(p3_c.x + x, p1_c.y - y, &color.set_a(c as u8));
-                    self.point(p1_c.x - x, p3_c.y + y, &color.set_a(c as u8));
-                    self.point(p3_c.x + x, p3_c.y + y, &color.set_a(c as u8));
+
+        let height = text_height + (lines.len() as i32 - 1) * line_height;
+        let mut y = rect.y
+            + font.ascent()
+            + match style.vertical_align {
+                VerticalAlign::Top => 0,
+                VerticalAlign::Middle => (rect.h as i32 - height) / 2,
+                VerticalAlign::Bottom => rect.h as i32 - height,
+            };
+        let space = space(font);
+        for line in lines {
+            let extra = max_width - line.width;
+            let mut x = rect.x
+                + match style.align {
+                    TextAlign::Left | TextAlign::Justify => 0,
+                    TextAlign::Center => extra / 2,
+                    TextAlign::Right => extra,
+                };
+            let gaps = line.words.len() as i32 - 1;
+            let justify = matches!(style.align, TextAlign::Justify) && !line.last && gaps > 0;
+            for (i, word) in line.words.iter().enumerate() {
+                text_run(
+                    self,
+                    Offset::new(x, y),
+                    word,
+                    font,
+                    Some(rect),
+                    &style.color,
+                );
+                x += width(font, word) + space;
+                if justify {
+                    let extra = extra.max(0);
+                    x += extra / gaps + ((i as i32) < extra % gaps) as i32;
                 }
             }
+            y += line_height;
+        }
+    }
+
+    /// Draws an aliased, filled polygon. The outline is closed automatically, and vertices lie on pixel corners, like [`Rect`] borders.
+    fn fill_polygon(&self, points: &[Offset], rule: FillRule, color: Color) {
+        let contour = points.iter().map(|p| (p.x as f32, p.y as f32)).collect();
+        rasterize(
+            &[contour],
+            rule,
+            false,
+            self.get_subregion().size(),
+            |x, y, _| self.point(x, y, &color),
+        );
+    }
+
+    /// Draws an antialiased, filled polygon. The outline is closed automatically, and vertices lie on pixel corners, like [`Rect`] borders.
+    fn fill_polygon_aa(&self, points: &[Offset], rule: FillRule, color: Color) {
+        let contour = points.iter().map(|p| (p.x as f32, p.y as f32)).collect();
+        rasterize(
+            &[contour],
+            rule,
+            true,
+            self.get_subregion().size(),
+            |x, y, c| plot_coverage(self, x, y, c, &color),
+        );
+    }
+
+    /// Draws an antialiased, filled path. Curves are flattened, and open subpaths are closed implicitly.
+    fn fill_path(&self, path: &Path, rule: FillRule, color: Color) {
+        let contours: Vec<_> = path
+            .flatten()
+            .into_iter()
+            .map(|(points, _)| points)
+            .collect();
+        rasterize(
+            &contours,
+            rule,
+            true,
+            self.get_subregion().size(),
+            |x, y, c| plot_coverage(self, x, y, c, &color),
+        );
+    }
+
+    /// Draws the outline of a path with an antialiased, one pixel wide line.
+    fn stroke_path(&self, path: &Path, color: Color) {
+        self.stroke_path_styled(path, &StrokeStyle::default(), color);
+    }
+
+    /// Draws the outline of a path with an antialiased line in the given style.
+    fn stroke_path_styled(&self, path: &Path, style: &StrokeStyle, color: Color) {
+        let outline = path.stroke_outline(style);
+        rasterize(
+            &outline,
+            FillRule::NonZero,
+            true,
+            self.get_subregion().size(),
+            |x, y, c| plot_coverage(self, x, y, c, &color),
+        );
+    }
+
+    /// Draws an antialiased line in the given style, through the same pixel centers as [`Self::line`].
+    fn line_styled(&self, p1: Offset, p2: Offset, style: &StrokeStyle, color: Color) {
+        let mut path = Path::new();
+        path.move_to(p1.x as f32 + 0.5, p1.y as f32 + 0.5)
+            .line_to(p2.x as f32 + 0.5, p2.y as f32 + 0.5);
+        self.stroke_path_styled(&path, style, color);
+    }
+
+    /// Draws an antialiased circle in the given style, centered on the outline of [`Self::circle_aa`].
+    fn circle_styled(&self, center: Offset, radius: u32, style: &StrokeStyle, color: Color) {
+        let (cx, cy) = (center.x as f32 + 0.5, center.y as f32 + 0.5);
+        let mut path = Path::new();
+        path.arc(cx, cy, radius as f32, radius as f32, 0., TAU32)
+            .close();
+        self.stroke_path_styled(&path, style, color);
+    }
+
+    /// Draws an antialiased circle arc in the given style, from angle1 to angle2 in radians, with positive angles measured counterclockwise from positive x axis.
+    fn circle_arc_styled(
+        &self,
+        center: Offset,
+        radius: u32,
+        angle1: f32,
+        angle2: f32,
+        style: &StrokeStyle,
+        color: Color,
+    ) {
+        let (cx, cy) = (center.x as f32 + 0.5, center.y as f32 + 0.5);
+        let sweep = if angle2 - angle1 >= TAU32 {
+            TAU32
+        } else {
+            (angle2 - angle1).rem_euclid(TAU32)
+        };
+        let mut path = Path::new();
+        path.arc(cx, cy, radius as f32, radius as f32, angle1, sweep);
+        if sweep == TAU32 {
+            path.close();
+        }
+        self.stroke_path_styled(&path, style, color);
+    }
+
+    /// Draws a rectangle border in the given style, centered on the border drawn by [`Self::rect`].
+    fn rect_styled(&self, rect: Rect, style: &StrokeStyle, color: Color) {
+        if rect.w == 0 || rect.h == 0 {
+            return;
+        }
+        let mut path = Path::new();
+        path.rect(
+            rect.x as f32 + 0.5,
+            rect.y as f32 + 0.5,
+            rect.w as f32 - 1.,
+            rect.h as f32 - 1.,
+        );
+        self.stroke_path_styled(&path, style, color);
+    }
+
+    /// Draws an antialiased rounded rectangle border in the given style, centered on the border drawn by [`Self::round_rect_aa`].
+    fn round_rect_styled(
+        &self,
+        rect: Rect,
+        radii: impl Into<CornerRadii>,
+        style: &StrokeStyle,
+        color: Color,
+    ) where
+        Self: Sized,
+    {
+        if rect.w == 0 || rect.h == 0 {
+            return;
+        }
+        let mut path = Path::new();
+        path.round_rect(
+            rect.x as f32 + 0.5,
+            rect.y as f32 + 0.5,
+            rect.w as f32 - 1.,
+            rect.h as f32 - 1.,
+            radii.into(),
+        );
+        self.stroke_path_styled(&path, style, color);
+    }
+
+    /// Draws an antialiased ellipse in the given style, centered on the outline of [`Self::ellipse_aa`].
+    fn ellipse_styled(&self, center: Offset, radius: Radius, style: &StrokeStyle, color: Color) {
+        let (cx, cy) = (center.x as f32 + 0.5, center.y as f32 + 0.5);
+        let mut path = Path::new();
+        path.arc(cx, cy, radius.x as f32, radius.y as f32, 0., TAU32)
+            .close();
+        self.stroke_path_styled(&path, style, color);
+    }
+
+    /// Draws an antialiased ellipse arc in the given style, with angles like [`Self::ellipse_arc`].
+    fn ellipse_arc_styled(
+        &self,
+        center: Offset,
+        radius: Radius,
+        angle1: f32,
+        angle2: f32,
+        style: &StrokeStyle,
+        color: Color,
+    ) {
+        let (cx, cy) = (center.x as f32 + 0.5, center.y as f32 + 0.5);
+        let (rx, ry) = (radius.x as f32, radius.y as f32);
+        // Paths take parametric angles, which differ from polar ones on ellipses.
+        let param = |a: f32| (a.sin() * rx).atan2(a.cos() * ry);
+        let start = param(angle1);
+        let sweep = if angle2 - angle1 >= TAU32 {
+            TAU32
+        } else {
+            (param(angle2) - start).rem_euclid(TAU32)
+        };
+        let mut path = Path::new();
+        path.arc(cx, cy, rx, ry, start, sweep);
+        if sweep == TAU32 {
+            path.close();
         }
+        self.stroke_path_styled(&path, style, color);
     }
 }
 
@@ -749,6 +1385,7 @@ pub struct Buffer {
     pub(crate) width: usize,
     pub(crate) height: usize,
     pub(crate) subregions: Vec<Rect>,
+    pub(crate) blend_mode: BlendMode,
 }
 
 impl Buffer {
@@ -759,6 +1396,7 @@ impl Buffer {
             width,
             height,
             subregions: vec![Size::new(width as _, height as _).into()],
+            blend_mode: BlendMode::Normal,
         }
     }
     pub fn data(&self) -> std::cell::Ref<'_, Vec<u8>> {
@@ -784,6 +1422,14 @@ impl Drawable for Buffer {
         *self.subregions.last().unwrap()
     }
 
+    fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode = mode;
+    }
+
+    fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
     fn size(&self) -> Size {
         Size {
             w: self.width as u32,
@@ -797,11 +1443,14 @@ impl Drawable for Buffer {
         if x < 0 || y < 0 || x as u32 >= subregion.w || y as u32 >= subregion.h {
             return;
         }
-        let [r, g, b, a] = color.get(Offset { x: x_o, y: y_o }).into();
+        let rgba = color.get(Offset { x: x_o, y: y_o });
+        let [r, g, b, a] = rgba.into();
         let (x, y) = (x_o as usize, y_o as usize);
         let pixel_range =
             &mut self.data.borrow_mut()[(x + y * self.width) * 3..(x + y * self.width) * 3 + 3];
-        if a == 255 {
+        if self.blend_mode != BlendMode::Normal {
+            self.blend_mode.blend_rgb(pixel_range, rgba);
+        } else if a == 255 {
             // Quick optimization
             pixel_range.copy_from_slice(&[r, g, b]);
         } else {
@@ -820,9 +1469,10 @@ pub struct Overlay {
     base_width: usize,
     base_height: usize,
     // Premultiplied RGB + Alpha
-    overlay_data: Rc<RefCell<Vec<u8>>>,
-    dst_rect: Rect,
+    pub(crate) overlay_data: Rc<RefCell<Vec<u8>>>,
+    pub(crate) dst_rect: Rect,
     subregions: Vec<Rect>,
+    blend_mode: BlendMode,
 }
 
 impl Overlay {
@@ -835,6 +1485,7 @@ impl Overlay {
             overlay_data: overlay,
             dst_rect: rect,
             subregions: vec![rect.size().into()],
+            blend_mode: BlendMode::Normal,
         }
     }
     pub fn offset(&mut self, offset: Offset) {
@@ -842,8 +1493,58 @@ impl Overlay {
         self.dst_rect.y = offset.y;
     }
 
+    /// Makes the overlay fully transparent.
+    pub fn clear(&self) {
+        self.overlay_data.borrow_mut().fill(0);
+    }
+
     /// Writes to the underlying buffer, and returns a copy.
     pub fn write(&self) -> Buffer {
+        self.write_with(CompositeOp::SrcOver, 255)
+    }
+
+    /// Writes to the underlying buffer with a compositing operator, fading the overlay by `opacity`, and returns a copy.
+    ///
+    /// The buffer is opaque, so it counts as a destination with full alpha.
+    pub fn write_with(&self, op: CompositeOp, opacity: u8) -> Buffer {
+        self.composite(|dst, src| {
+            let [r, g, b, a] = src.map(|c| (c as u16 * opacity as u16 / 255) as u8);
+            if a == 255 && op == CompositeOp::SrcOver {
+                // Quick optimization
+                dst.copy_from_slice(&[r, g, b]);
+                return;
+            }
+            let (src_f, dst_f) = op.factors(a, 255);
+            let (src_f, dst_f) = (src_f as i32, dst_f as i32);
+            for (dst, src) in dst.iter_mut().zip([r, g, b]) {
+                *dst = (src as i32 * src_f / 255 + dst_f * *dst as i32 / 255).min(255) as u8;
+            }
+        })
+    }
+
+    /// Writes to the underlying buffer with a blend mode, fading the overlay by `opacity`, and returns a copy.
+    pub fn write_blended(&self, mode: BlendMode, opacity: u8) -> Buffer {
+        if mode == BlendMode::Normal {
+            return self.write_with(CompositeOp::SrcOver, opacity);
+        }
+        self.composite(|dst, [r, g, b, a]| {
+            if a == 0 {
+                return;
+            }
+            let unpremultiply = |c: u8| (c as u16 * 255 / a as u16).min(255) as u8;
+            let a = (a as u16 * opacity as u16 / 255) as u8;
+            let src = Rgba {
+                r: unpremultiply(r),
+                g: unpremultiply(g),
+                b: unpremultiply(b),
+                a,
+            };
+            mode.blend_rgb(dst, src);
+        })
+    }
+
+    /// Calls `f` with every pixel of the underlying buffer under the overlay, and the overlay's pixel. Returns a copy of the buffer.
+    fn composite<F: FnMut(&mut [u8], [u8; 4])>(&self, mut f: F) -> Buffer {
         let mut base = self.base.borrow_mut();
         let overlay = self.overlay_data.borrow();
         let src_offs = -self.dst_rect.offset().min(Offset::default());
@@ -863,20 +1564,12 @@ impl Overlay {
 
                 let overlay_offs = src_offs + (Offset::new(i as _, j as _) - dst_rect.offset());
                 let overlay_array_offs =
-                    4 * (overlay_offs.x + overlay_offs.y * dst_rect.w as i32) as usize;
+                    4 * (overlay_offs.x + overlay_offs.y * self.dst_rect.w as i32) as usize;
 
                 let [r, g, b, a] = overlay[overlay_array_offs..overlay_array_offs + 4] else {
                     unreachable!()
                 };
-                if a == 255 {
-                    // Quick optimization
-                    base[offs..offs + 3].copy_from_slice(&[r, g, b]);
-                    continue;
-                }
-                let (r, g, b, a) = (r as i32, g as i32, b as i32, a as i32);
-                base[offs] = ((255 - a) * base[offs] as i32 / 255 + r) as u8;
-                base[offs + 1] = ((255 - a) * base[offs + 1] as i32 / 255 + g) as u8;
-                base[offs + 2] = ((255 - a) * base[offs + 2] as i32 / 255 + b) as u8;
+                f(&mut base[offs..offs + 3], [r, g, b, a]);
             }
         }
         mem::drop(base);
@@ -885,6 +1578,7 @@ impl Overlay {
             width: self.base_width,
             height: self.base_height,
             subregions: vec![Size::new(self.base_width as _, self.base_height as _).into()],
+            blend_mode: BlendMode::Normal,
         }
     }
 }
@@ -907,6 +1601,14 @@ impl Drawable for Overlay {
         *self.subregions.last().unwrap()
     }
 
+    fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode = mode;
+    }
+
+    fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
     fn size(&self) -> Size {
         Size {
             w: self.base_width as u32,
@@ -920,11 +1622,14 @@ impl Drawable for Overlay {
         if x < 0 || y < 0 || x as u32 >= subregion.w || y as u32 >= subregion.h {
             return;
         }
-        let [r, g, b, a] = color.get(Offset { x: x_o, y: y_o }).into();
+        let rgba = color.get(Offset { x: x_o, y: y_o });
+        let [r, g, b, a] = rgba.into();
         let (x, y) = (x_o as usize, y_o as usize);
         let pixel_range = &mut self.overlay_data.borrow_mut()
             [(x + y * self.dst_rect.w as usize) * 4..(x + y * self.dst_rect.w as usize) * 4 + 4];
-        if a == 255 {
+        if self.blend_mode != BlendMode::Normal {
+            self.blend_mode.blend_premultiplied(pixel_range, rgba);
+        } else if a == 255 {
             // Quick optimization
             pixel_range.copy_from_slice(&[r, g, b, a]);
         } else {
@@ -0,0 +1,416 @@
+use std::collections::HashMap;
+
+use super::{over_background, premultiplied_rgba, straight_rgba, ImageError};
+use crate::{color::Rgba, Buffer, Overlay};
+
+const BI_RGB: u32 = 0;
+const BI_RLE8: u32 = 1;
+const BI_RLE4: u32 = 2;
+const BI_BITFIELDS: u32 = 3;
+const BI_ALPHABITFIELDS: u32 = 6;
+
+/// Writes the file header and a `BITMAPINFOHEADER`, or a `BITMAPV4HEADER` with color masks.
+fn write_headers(
+    out: &mut Vec<u8>,
+    size: (usize, usize),
+    bits: u16,
+    compression: u32,
+    palette: &[[u8; 3]],
+    image_size: usize,
+) {
+    let info_size = if compression == BI_BITFIELDS { 108 } else { 40 };
+    let offset = 14 + info_size + palette.len() * 4;
+    out.extend_from_slice(b"BM");
+    out.extend_from_slice(&((offset + image_size) as u32).to_le_bytes());
+    out.extend_from_slice(&[0; 4]);
+    out.extend_from_slice(&(offset as u32).to_le_bytes());
+
+    out.extend_from_slice(&(info_size as u32).to_le_bytes());
+    out.extend_from_slice(&(size.0 as i32).to_le_bytes());
+    out.extend_from_slice(&(size.1 as i32).to_le_bytes());
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&bits.to_le_bytes());
+    out.extend_from_slice(&compression.to_le_bytes());
+    out.extend_from_slice(&(image_size as u32).to_le_bytes());
+    // 72 DPI.
+    out.extend_from_slice(&2835u32.to_le_bytes());
+    out.extend_from_slice(&2835u32.to_le_bytes());
+    out.extend_from_slice(&(palette.len() as u32).to_le_bytes());
+    out.extend_from_slice(&[0; 4]);
+    if compression == BI_BITFIELDS {
+        for mask in [0x00ff0000u32, 0x0000ff00, 0x000000ff, 0xff000000] {
+            out.extend_from_slice(&mask.to_le_bytes());
+        }
+        out.extend_from_slice(b"BGRs");
+        // Endpoints and gamma, unused with sRGB.
+        out.extend_from_slice(&[0; 48]);
+    }
+    for [r, g, b] in palette {
+        out.extend_from_slice(&[*b, *g, *r, 0]);
+    }
+}
+
+/// Encodes a row of palette indices with RLE8.
+fn rle8_row(out: &mut Vec<u8>, row: &[u8]) {
+    let mut i = 0;
+    while i < row.len() {
+        let run = row[i..]
+            .iter()
+            .take(255)
+            .take_while(|&&v| v == row[i])
+            .count();
+        if run >= 2 {
+            out.extend_from_slice(&[run as u8, row[i]]);
+            i += run;
+            continue;
+        }
+        // Gather pixels up to the next run.
+        let mut end = i + 1;
+        while end < row.len() && end - i < 255 && (end + 1 >= row.len() || row[end] != row[end + 1])
+        {
+            end += 1;
+        }
+        let literal = &row[i..end];
+        if literal.len() >= 3 {
+            out.extend_from_slice(&[0, literal.len() as u8]);
+            out.extend_from_slice(literal);
+            if literal.len() % 2 == 1 {
+                out.push(0);
+            }
+        } else {
+            for &v in literal {
+                out.extend_from_slice(&[1, v]);
+            }
+        }
+        i = end;
+    }
+    out.extend_from_slice(&[0, 0]);
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Result<u16, ImageError> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .ok_or(ImageError::Malformed("truncated header"))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, ImageError> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or(ImageError::Malformed("truncated header"))
+}
+
+/// Extracts a channel with a bit mask, scaled to 0-255.
+fn masked(value: u32, mask: u32) -> u8 {
+    if mask == 0 {
+        return 0;
+    }
+    let v = (value & mask) >> mask.trailing_zeros();
+    let max = mask >> mask.trailing_zeros();
+    ((v as u64 * 255 + max as u64 / 2) / max as u64) as u8
+}
+
+impl Buffer {
+    /// Encodes the buffer as an uncompressed 24-bit BMP.
+    pub fn to_bmp(&self) -> Vec<u8> {
+        let data = self.data.borrow();
+        let stride = (self.width * 3).next_multiple_of(4);
+        let mut out = Vec::with_capacity(54 + stride * self.height);
+        write_headers(
+            &mut out,
+            (self.width, self.height),
+            24,
+            BI_RGB,
+            &[],
+            stride * self.height,
+        );
+        for row in data.chunks_exact((self.width * 3).max(1)).rev() {
+            let start = out.len();
+            out.extend(row.chunks_exact(3).flat_map(|c| [c[2], c[1], c[0]]));
+            out.resize(start + stride, 0);
+        }
+        out
+    }
+
+    /// Encodes the buffer as an RLE8 compressed BMP, or `None` if it has more than 256 colors.
+    pub fn to_bmp_rle8(&self) -> Option<Vec<u8>> {
+        let data = self.data.borrow();
+        let mut palette = Vec::new();
+        let mut indices = HashMap::new();
+        let mut pixels = Vec::with_capacity(self.width * self.height);
+        for c in data.chunks_exact(3) {
+            let color = [c[0], c[1], c[2]];
+            let index = *indices.entry(color).or_insert_with(|| {
+                palette.push(color);
+                palette.len() - 1
+            });
+            if index > 255 {
+                return None;
+            }
+            pixels.push(index as u8);
+        }
+
+        let mut body = Vec::new();
+        for row in pixels.chunks_exact(self.width.max(1)).rev() {
+            rle8_row(&mut body, row);
+        }
+        body.extend_from_slice(&[0, 1]);
+        let mut out = Vec::new();
+        write_headers(
+            &mut out,
+            (self.width, self.height),
+            8,
+            BI_RLE8,
+            &palette,
+            body.len(),
+        );
+        out.extend_from_slice(&body);
+        Some(out)
+    }
+
+    /// Decodes a BMP, blending transparent pixels over white.
+    pub fn from_bmp(data: &[u8]) -> Result<Self, ImageError> {
+        Self::from_bmp_with_background(data, Rgba::WHITE)
+    }
+
+    /// Decodes a BMP, blending transparent pixels over `background`.
+    ///
+    /// Supports 1, 4, 8, 16, 24 and 32 bit pixels, uncompressed, with bit fields, or RLE4/RLE8 compressed.
+    pub fn from_bmp_with_background(data: &[u8], background: Rgba) -> Result<Self, ImageError> {
+        let malformed = ImageError::Malformed;
+        if data.get(..2) != Some(b"BM") {
+            return Err(malformed("not a BMP file"));
+        }
+        let offset = read_u32(data, 10)? as usize;
+        let info_size = read_u32(data, 14)? as usize;
+        let (width, height, bits, compression, colors_used) = if info_size == 12 {
+            let (w, h) = (
+                read_u16(data, 18)? as i32,
+                read_u16(data, 20)? as i16 as i32,
+            );
+            (w, h, read_u16(data, 24)?, BI_RGB, 0)
+        } else if info_size >= 40 {
+            (
+                read_u32(data, 18)? as i32,
+                read_u32(data, 22)? as i32,
+                read_u16(data, 28)?,
+                read_u32(data, 30)?,
+                read_u32(data, 46)? as usize,
+            )
+        } else {
+            return Err(ImageError::Unsupported("BMP header version"));
+        };
+        let (width, top_down) = (width.max(0) as usize, height < 0);
+        let height = height.unsigned_abs() as usize;
+
+        // Masks come in the header for newer versions, or right after it.
+        let (mut masks, mut palette_start) = ([0; 4], 14 + info_size);
+        if compression == BI_BITFIELDS || compression == BI_ALPHABITFIELDS {
+            let count = if compression == BI_ALPHABITFIELDS {
+                4
+            } else {
+                3
+            };
+            let at = if info_size >= 52 { 54 } else { palette_start };
+            for (i, mask) in masks.iter_mut().enumerate().take(count) {
+                *mask = read_u32(data, at + 4 * i)?;
+            }
+            if info_size >= 56 {
+                masks[3] = read_u32(data, 66)?;
+            }
+            if info_size < 52 {
+                palette_start += 4 * count;
+            }
+        } else if info_size >= 56 && bits == 32 {
+            masks = [0xff0000, 0xff00, 0xff, read_u32(data, 66)?];
+        } else if bits == 16 {
+            masks = [0x7c00, 0x3e0, 0x1f, 0];
+        } else if bits == 32 {
+            masks = [0xff0000, 0xff00, 0xff, 0];
+        }
+
+        let palette: Vec<[u8; 4]> = if bits <= 8 {
+            let entry = if info_size == 12 { 3 } else { 4 };
+            let count = if colors_used == 0 {
+                1 << bits
+            } else {
+                colors_used.min(256)
+            };
+            (0..count)
+                .map(|i| {
+                    let at = palette_start + i * entry;
+                    data.get(at..at + 3)
+                        .map(|c| [c[2], c[1], c[0], 255])
+                        .ok_or(malformed("truncated palette"))
+                })
+                .collect::<Result<_, _>>()?
+        } else {
+            Vec::new()
+        };
+        let color = |i: u8| palette.get(i as usize).copied().unwrap_or([0, 0, 0, 255]);
+        let body = data
+            .get(offset..)
+            .ok_or(malformed("truncated image data"))?;
+
+        if width == 0 || height == 0 {
+            return Ok(Buffer::new(width, height));
+        }
+        let count = width
+            .checked_mul(height)
+            .ok_or(malformed("image size too large"))?;
+
+        // Pixels from the bottom row up, unless top-down. Pixels skipped by RLE stay transparent.
+        let mut pixels;
+        match (compression, bits) {
+            (BI_RLE8, 8) | (BI_RLE4, 4) => {
+                // Each 2 byte code covers at most 255 pixels.
+                if count > body.len() / 2 * 255 {
+                    return Err(malformed("image size too large for the data"));
+                }
+                pixels = vec![[0u8; 4]; count];
+                let (mut x, mut y, mut i) = (0, 0, 0);
+                let mut put = |x: &mut usize, y: usize, index: u8| {
+                    if *x < width && y < height {
+                        pixels[*x + y * width] = color(index);
+                    }
+                    *x += 1;
+                };
+                while i + 1 < body.len() {
+                    let (count, value) = (body[i] as usize, body[i + 1]);
+                    i += 2;
+                    if count > 0 {
+                        for k in 0..count {
+                            let index = if bits == 8 {
+                                value
+                            } else if k % 2 == 0 {
+                                value >> 4
+                            } else {
+                                value & 0xf
+                            };
+                            put(&mut x, y, index);
+                        }
+                        continue;
+                    }
+                    match value {
+                        0 => (x, y) = (0, y + 1),
+                        1 => break,
+                        2 => {
+                            let delta =
+                                body.get(i..i + 2).ok_or(malformed("truncated RLE data"))?;
+                            (x, y) = (x + delta[0] as usize, y + delta[1] as usize);
+                            i += 2;
+                        }
+                        n => {
+                            let n = n as usize;
+                            let bytes = if bits == 8 { n } else { n.div_ceil(2) };
+                            let literal = body
+                                .get(i..i + bytes)
+                                .ok_or(malformed("truncated RLE data"))?;
+                            for k in 0..n {
+                                let index = if bits == 8 {
+                                    literal[k]
+                                } else if k % 2 == 0 {
+                                    literal[k / 2] >> 4
+                                } else {
+                                    literal[k / 2] & 0xf
+                                };
+                                put(&mut x, y, index);
+                            }
+                            // Literal runs are padded to 16 bits.
+                            i += bytes.next_multiple_of(2);
+                        }
+                    }
+                }
+            }
+            (BI_RGB | BI_BITFIELDS | BI_ALPHABITFIELDS, 1 | 2 | 4 | 8 | 16 | 24 | 32) => {
+                let bits = bits as usize;
+                let stride = width
+                    .checked_mul(bits)
+                    .map(|row_bits| row_bits.div_ceil(32) * 4)
+                    .filter(|stride| {
+                        stride
+                            .checked_mul(height)
+                            .is_some_and(|size| size <= body.len())
+                    })
+                    .ok_or(malformed("truncated image data"))?;
+                pixels = vec![[0u8; 4]; count];
+                for (y, row) in body.chunks_exact(stride).take(height).enumerate() {
+                    for x in 0..width {
+                        pixels[x + y * width] = match bits {
+                            24 => [row[x * 3 + 2], row[x * 3 + 1], row[x * 3], 255],
+                            16 | 32 => {
+                                let at = x * bits / 8;
+                                let v = if bits == 16 {
+                                    u16::from_le_bytes([row[at], row[at + 1]]) as u32
+                                } else {
+                                    u32::from_le_bytes([
+                                        row[at],
+                                        row[at + 1],
+                                        row[at + 2],
+                                        row[at + 3],
+                                    ])
+                                };
+                                let a = if masks[3] == 0 {
+                                    255
+                                } else {
+                                    masked(v, masks[3])
+                                };
+                                [
+                                    masked(v, masks[0]),
+                                    masked(v, masks[1]),
+                                    masked(v, masks[2]),
+                                    a,
+                                ]
+                            }
+                            _ => {
+                                let bit = x * bits;
+                                color(
+                                    (row[bit / 8] >> (8 - bits - bit % 8))
+                                        & ((1 << bits) - 1) as u8,
+                                )
+                            }
+                        };
+                    }
+                }
+                // Some writers leave alpha at zero everywhere, meaning opaque.
+                if masks[3] != 0 && pixels.iter().all(|p| p[3] == 0) {
+                    pixels.iter_mut().for_each(|p| p[3] = 255);
+                }
+            }
+            _ => return Err(ImageError::Unsupported("BMP compression or bit depth")),
+        }
+
+        let buf = Buffer::new(width, height);
+        let mut out = buf.data.borrow_mut();
+        for y in 0..height {
+            let src = if top_down { y } else { height - 1 - y };
+            for x in 0..width {
+                let rgb = over_background(pixels[x + src * width], background);
+                out[(x + y * width) * 3..(x + y * width) * 3 + 3].copy_from_slice(&rgb);
+            }
+        }
+        drop(out);
+        Ok(buf)
+    }
+}
+
+impl Overlay {
+    /// Encodes the overlay as a 32-bit BMP, converting its pixels to straight alpha.
+    pub fn to_bmp(&self) -> Vec<u8> {
+        self.to_bmp32(&straight_rgba(self))
+    }
+
+    /// Encodes the overlay as a 32-bit BMP, keeping its pixels premultiplied as stored.
+    pub fn to_bmp_premultiplied(&self) -> Vec<u8> {
+        self.to_bmp32(&premultiplied_rgba(self))
+    }
+
+    fn to_bmp32(&self, pixels: &[[u8; 4]]) -> Vec<u8> {
+        let (w, h) = (self.dst_rect.w as usize, self.dst_rect.h as usize);
+        let mut out = Vec::with_capacity(122 + w * h * 4);
+        write_headers(&mut out, (w, h), 32, BI_BITFIELDS, &[], w * h * 4);
+        for row in pixels.chunks_exact(w.max(1)).rev() {
+            out.extend(row.iter().flat_map(|&[r, g, b, a]| [b, g, r, a]));
+        }
+        out
+    }
+}
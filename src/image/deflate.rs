@@ -0,0 +1,444 @@
+//! Minimal zlib (deflate) compression with fixed Huffman codes, and full decompression.
+
+use super::ImageError;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// Order of code length code lengths in dynamic blocks.
+const CL_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+const WINDOW: usize = 1 << 15;
+const MAX_MATCH: usize = 258;
+const MAX_CHAIN: usize = 64;
+
+struct BitWriter {
+    out: Vec<u8>,
+    acc: u32,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, bits: u32) {
+        self.acc |= value << self.bits;
+        self.bits += bits;
+        while self.bits >= 8 {
+            self.out.push(self.acc as u8);
+            self.acc >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    /// Huffman codes are packed starting from their most significant bit.
+    fn write_code(&mut self, code: u32, bits: u32) {
+        self.write(code.reverse_bits() >> (32 - bits), bits);
+    }
+
+    fn write_literal(&mut self, symbol: u16) {
+        match symbol {
+            0..=143 => self.write_code(0x30 + symbol as u32, 8),
+            144..=255 => self.write_code(0x190 + symbol as u32 - 144, 9),
+            256..=279 => self.write_code(symbol as u32 - 256, 7),
+            _ => self.write_code(0xc0 + symbol as u32 - 280, 8),
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.out.push(self.acc as u8);
+        }
+        self.out
+    }
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    b << 16 | a
+}
+
+/// Compresses into a zlib stream.
+pub(crate) fn zlib_compress(data: &[u8]) -> Vec<u8> {
+    let mut w = BitWriter {
+        out: vec![0x78, 0x01],
+        acc: 0,
+        bits: 0,
+    };
+    // A single final block with fixed codes.
+    w.write(1, 1);
+    w.write(1, 2);
+
+    let hash = |i: usize| {
+        ((data[i] as usize) << 10 ^ (data[i + 1] as usize) << 5 ^ data[i + 2] as usize)
+            & (WINDOW - 1)
+    };
+    let mut head = vec![usize::MAX; WINDOW];
+    let mut prev = vec![usize::MAX; WINDOW];
+    let insert = |i: usize, head: &mut [usize], prev: &mut [usize]| {
+        if i + 2 < data.len() {
+            let h = hash(i);
+            prev[i % WINDOW] = head[h];
+            head[h] = i;
+        }
+    };
+
+    let mut i = 0;
+    while i < data.len() {
+        let (mut best_len, mut best_dist) = (0, 0);
+        if i + 2 < data.len() {
+            let mut candidate = head[hash(i)];
+            let max = MAX_MATCH.min(data.len() - i);
+            for _ in 0..MAX_CHAIN {
+                if candidate == usize::MAX || i - candidate > WINDOW - 1 {
+                    break;
+                }
+                let len = (0..max)
+                    .take_while(|&k| data[candidate + k] == data[i + k])
+                    .count();
+                if len > best_len {
+                    (best_len, best_dist) = (len, i - candidate);
+                    if len == max {
+                        break;
+                    }
+                }
+                let next = prev[candidate % WINDOW];
+                if next == usize::MAX || next >= candidate {
+                    break;
+                }
+                candidate = next;
+            }
+        }
+
+        if best_len >= 3 {
+            let code = LENGTH_BASE.partition_point(|&b| b as usize <= best_len) - 1;
+            w.write_literal(257 + code as u16);
+            w.write(
+                (best_len - LENGTH_BASE[code] as usize) as u32,
+                LENGTH_EXTRA[code] as u32,
+            );
+            let code = DIST_BASE.partition_point(|&b| b as usize <= best_dist) - 1;
+            w.write_code(code as u32, 5);
+            w.write(
+                (best_dist - DIST_BASE[code] as usize) as u32,
+                DIST_EXTRA[code] as u32,
+            );
+            for k in i..i + best_len {
+                insert(k, &mut head, &mut prev);
+            }
+            i += best_len;
+        } else {
+            w.write_literal(data[i] as u16);
+            insert(i, &mut head, &mut prev);
+            i += 1;
+        }
+    }
+    w.write_literal(256);
+
+    let mut out = w.finish();
+    out.extend_from_slice(&adler32(data).to_be_bytes());
+    out
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    acc: u32,
+    bits: u32,
+}
+
+impl BitReader<'_> {
+    fn read(&mut self, bits: u32) -> Result<u32, ImageError> {
+        while self.bits < bits {
+            let byte = *self
+                .data
+                .get(self.pos)
+                .ok_or(ImageError::Malformed("truncated deflate stream"))?;
+            self.pos += 1;
+            self.acc |= (byte as u32) << self.bits;
+            self.bits += 8;
+        }
+        let value = self.acc & ((1u64 << bits) - 1) as u32;
+        self.acc >>= bits;
+        self.bits -= bits;
+        Ok(value)
+    }
+
+    fn align(&mut self) {
+        self.acc = 0;
+        self.bits = 0;
+    }
+}
+
+/// A canonical Huffman code, as counts of codes per length and symbols ordered by code.
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Self {
+        let mut counts = [0; 16];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+        counts[0] = 0;
+        let mut offsets = [0; 16];
+        for len in 1..16 {
+            offsets[len] = offsets[len - 1] + counts[len - 1];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+        Self { counts, symbols }
+    }
+
+    fn decode(&self, r: &mut BitReader) -> Result<u16, ImageError> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..16 {
+            code |= r.read(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(ImageError::Malformed("invalid Huffman code"))
+    }
+}
+
+/// Decompresses a zlib stream, failing if the output would exceed `limit` bytes.
+pub(crate) fn zlib_decompress(data: &[u8], limit: usize) -> Result<Vec<u8>, ImageError> {
+    let malformed = ImageError::Malformed("invalid zlib stream");
+    let [cmf, flg, ..] = *data else {
+        return Err(malformed);
+    };
+    if cmf & 0x0f != 8 || !(cmf as u16 * 256 + flg as u16).is_multiple_of(31) || flg & 0x20 != 0 {
+        return Err(malformed);
+    }
+
+    let mut r = BitReader {
+        data: &data[2..],
+        pos: 0,
+        acc: 0,
+        bits: 0,
+    };
+    let mut out = Vec::new();
+    loop {
+        let last = r.read(1)? == 1;
+        match r.read(2)? {
+            0 => {
+                r.align();
+                let header = r.data.get(r.pos..r.pos + 4).ok_or(malformed.clone())?;
+                let len = u16::from_le_bytes([header[0], header[1]]);
+                if len != !u16::from_le_bytes([header[2], header[3]]) {
+                    return Err(malformed);
+                }
+                let len = len as usize;
+                r.pos += 4;
+                let stored = r.data.get(r.pos..r.pos + len).ok_or(malformed.clone())?;
+                if out.len() + len > limit {
+                    return Err(ImageError::Malformed("decompressed data too large"));
+                }
+                out.extend_from_slice(stored);
+                r.pos += len;
+            }
+            1 => {
+                let mut lengths = [8; 288];
+                lengths[144..256].fill(9);
+                lengths[256..280].fill(7);
+                inflate_block(
+                    &mut r,
+                    &mut out,
+                    &Huffman::new(&lengths),
+                    &Huffman::new(&[5; 30]),
+                    limit,
+                )?;
+            }
+            2 => {
+                let literals = r.read(5)? as usize + 257;
+                let distances = r.read(5)? as usize + 1;
+                let code_lengths = r.read(4)? as usize + 4;
+                let mut cl = [0; 19];
+                for &i in &CL_ORDER[..code_lengths] {
+                    cl[i] = r.read(3)? as u8;
+                }
+                let cl = Huffman::new(&cl);
+                let mut lengths = Vec::with_capacity(literals + distances);
+                while lengths.len() < literals + distances {
+                    let (value, repeat) = match cl.decode(&mut r)? {
+                        symbol @ 0..=15 => (symbol as u8, 1),
+                        16 => (*lengths.last().ok_or(malformed.clone())?, 3 + r.read(2)?),
+                        17 => (0, 3 + r.read(3)?),
+                        _ => (0, 11 + r.read(7)?),
+                    };
+                    lengths.extend((0..repeat).map(|_| value));
+                }
+                if lengths.len() > literals + distances {
+                    return Err(malformed);
+                }
+                let (lit, dist) = lengths.split_at(literals);
+                inflate_block(
+                    &mut r,
+                    &mut out,
+                    &Huffman::new(lit),
+                    &Huffman::new(dist),
+                    limit,
+                )?;
+            }
+            _ => return Err(malformed),
+        }
+        if last {
+            break;
+        }
+    }
+    r.align();
+    let checksum = r.data.get(r.pos..r.pos + 4).ok_or(malformed)?;
+    if adler32(&out).to_be_bytes() != checksum {
+        return Err(ImageError::Malformed("zlib checksum mismatch"));
+    }
+    Ok(out)
+}
+
+fn inflate_block(
+    r: &mut BitReader,
+    out: &mut Vec<u8>,
+    literals: &Huffman,
+    distances: &Huffman,
+    limit: usize,
+) -> Result<(), ImageError> {
+    let malformed = ImageError::Malformed("invalid deflate block");
+    let too_large = ImageError::Malformed("decompressed data too large");
+    loop {
+        let symbol = literals.decode(r)? as usize;
+        match symbol {
+            0..=255 if out.len() >= limit => return Err(too_large),
+            0..=255 => out.push(symbol as u8),
+            256 => return Ok(()),
+            257..=285 => {
+                let code = symbol - 257;
+                let len = LENGTH_BASE[code] as usize + r.read(LENGTH_EXTRA[code] as u32)? as usize;
+                let code = distances.decode(r)? as usize;
+                if code >= 30 {
+                    return Err(malformed);
+                }
+                let dist = DIST_BASE[code] as usize + r.read(DIST_EXTRA[code] as u32)? as usize;
+                if dist > out.len() {
+                    return Err(malformed);
+                }
+                if out.len() + len > limit {
+                    return Err(too_large);
+                }
+                let start = out.len() - dist;
+                for k in 0..len {
+                    out.push(out[start + k]);
+                }
+            }
+            _ => return Err(malformed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `zlib.compress(data, 9)` in Python of the squares from 0 to 119 separated by spaces, using a dynamic Huffman block.
+    const SQUARES: &[u8] = include_bytes!("../../tests/data/squares.zlib");
+    /// `zlib.compress(b"stored", 0)` in Python, using a stored block.
+    const STORED: &[u8] = b"\x78\x01\x01\x06\x00\xf9\xffstored\x09\x3c\x02\x92";
+
+    fn squares() -> Vec<u8> {
+        let squares: Vec<_> = (0..120).map(|i: u32| (i * i).to_string()).collect();
+        squares.join(" ").into_bytes()
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut noise = Vec::new();
+        let mut x = 1u32;
+        for _ in 0..100_000 {
+            x = x.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            noise.push((x >> 16) as u8);
+        }
+        for data in [Vec::new(), b"a".to_vec(), vec![7; 70_000], squares(), noise] {
+            let compressed = zlib_compress(&data);
+            assert_eq!(zlib_decompress(&compressed, data.len()).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn reference_streams() {
+        assert_eq!(zlib_decompress(SQUARES, usize::MAX).unwrap(), squares());
+        assert_eq!(zlib_decompress(STORED, usize::MAX).unwrap(), b"stored");
+    }
+
+    #[test]
+    fn limit() {
+        let len = squares().len();
+        assert!(zlib_decompress(SQUARES, len - 1).is_err());
+        assert!(zlib_decompress(STORED, 5).is_err());
+    }
+
+    #[test]
+    fn malformed() {
+        assert!(zlib_decompress(&[], usize::MAX).is_err());
+        assert!(zlib_decompress(&SQUARES[..SQUARES.len() / 2], usize::MAX).is_err());
+        // Missing checksum.
+        assert!(zlib_decompress(&STORED[..STORED.len() - 4], usize::MAX).is_err());
+
+        let mut bad = SQUARES.to_vec();
+        bad[0] = 0x79;
+        assert!(zlib_decompress(&bad, usize::MAX).is_err());
+
+        let mut bad = SQUARES.to_vec();
+        *bad.last_mut().unwrap() ^= 1;
+        assert!(zlib_decompress(&bad, usize::MAX).is_err());
+
+        // NLEN isn't the complement of LEN.
+        let mut bad = STORED.to_vec();
+        bad[5] = 0;
+        assert!(zlib_decompress(&bad, usize::MAX).is_err());
+
+        // Reserved block type.
+        assert!(zlib_decompress(b"\x78\x01\x07\x00", usize::MAX).is_err());
+        // Distance before the start of the output.
+        let mut w = BitWriter {
+            out: vec![0x78, 0x01],
+            acc: 0,
+            bits: 0,
+        };
+        w.write(1, 1);
+        w.write(1, 2);
+        w.write_literal(257);
+        w.write_code(0, 5);
+        w.write_literal(256);
+        assert!(zlib_decompress(&w.finish(), usize::MAX).is_err());
+    }
+}
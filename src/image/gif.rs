@@ -0,0 +1,317 @@
+use std::{collections::HashMap, io, path::Path, time::Duration};
+
+use crate::{
+    color::Rgba,
+    dither::{dither, DitherMode, Quantizer},
+    Buffer,
+};
+
+/// Where the colors of a GIF are stored.
+#[derive(Clone, Copy, Default)]
+pub enum GifPalette {
+    /// One palette chosen from all frames.
+    #[default]
+    Global,
+    /// A palette for each frame, chosen from the area that changed.
+    PerFrame,
+}
+
+/// Encodes a sequence of buffers as an animated GIF.
+///
+/// Each frame only stores the rectangle that differs from the previous one, and frames
+/// identical to the previous one extend its delay.
+pub struct GifEncoder {
+    width: usize,
+    height: usize,
+    loop_count: Option<u16>,
+    palette: GifPalette,
+    quantizer: Quantizer,
+    dither: DitherMode,
+    frames: Vec<(Vec<u8>, Duration)>,
+}
+
+/// A frame ready to be written.
+struct Frame {
+    /// Left, top, width and height on the screen.
+    rect: [usize; 4],
+    /// Local color table, if not using the global one.
+    palette: Option<Vec<Rgba>>,
+    indices: Vec<u8>,
+    /// In hundredths of a second.
+    delay: u16,
+}
+
+/// Writes codes of varying sizes, least significant bit first.
+struct BitWriter {
+    out: Vec<u8>,
+    bits: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.bits |= (code as u32) << self.count;
+        self.count += size as u32;
+        while self.count >= 8 {
+            self.out.push(self.bits as u8);
+            self.bits >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.out.push(self.bits as u8);
+        }
+        self.out
+    }
+}
+
+/// Compresses palette indices with the variable length LZW codes of GIF.
+fn lzw(indices: &[u8], min_size: u8) -> Vec<u8> {
+    let clear = 1u16 << min_size;
+    let end = clear + 1;
+    let mut table = HashMap::new();
+    let (mut next, mut size) = (end + 1, min_size + 1);
+    let mut writer = BitWriter {
+        out: Vec::new(),
+        bits: 0,
+        count: 0,
+    };
+    writer.write(clear, size);
+
+    let mut prefix = None;
+    for &k in indices {
+        let Some(p) = prefix else {
+            prefix = Some(k as u16);
+            continue;
+        };
+        if let Some(&code) = table.get(&(p, k)) {
+            prefix = Some(code);
+            continue;
+        }
+        writer.write(p, size);
+        if next == 4096 {
+            writer.write(clear, size);
+            table.clear();
+            (next, size) = (end + 1, min_size + 1);
+        } else {
+            table.insert((p, k), next);
+            next += 1;
+            // Decoders add codes one step later, so grow once the code just added needs another bit.
+            if next > 1 << size && size < 12 {
+                size += 1;
+            }
+        }
+        prefix = Some(k as u16);
+    }
+    if let Some(p) = prefix {
+        writer.write(p, size);
+    }
+    writer.write(end, size);
+    writer.finish()
+}
+
+/// Number of bits for a color table holding the palette, at least 1.
+fn table_bits(palette: &[Rgba]) -> u8 {
+    (palette.len().max(2).next_power_of_two().trailing_zeros()) as u8
+}
+
+fn write_table(out: &mut Vec<u8>, palette: &[Rgba]) {
+    for c in palette {
+        out.extend_from_slice(&[c.r, c.g, c.b]);
+    }
+    out.resize(
+        out.len() + ((1 << table_bits(palette)) - palette.len()) * 3,
+        0,
+    );
+}
+
+impl GifEncoder {
+    /// Creates an encoder for frames of `width` by `height`.
+    ///
+    /// A `loop_count` of `Some(0)` loops forever, `None` plays the animation once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` or `height` exceeds 65535, the largest size a GIF can store.
+    pub fn new(
+        width: usize,
+        height: usize,
+        loop_count: Option<u16>,
+        palette: GifPalette,
+        quantizer: Quantizer,
+        dither: DitherMode,
+    ) -> Self {
+        assert!(
+            width <= u16::MAX as usize && height <= u16::MAX as usize,
+            "GIF size can't exceed 65535 by 65535"
+        );
+        Self {
+            width,
+            height,
+            loop_count,
+            palette,
+            quantizer,
+            dither,
+            frames: Vec::new(),
+        }
+    }
+
+    /// Adds a copy of the buffer as the next frame, shown for `delay`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the buffer's size differs from the encoder's.
+    pub fn add_frame(&mut self, frame: &Buffer, delay: Duration) {
+        assert!(
+            frame.width == self.width && frame.height == self.height,
+            "frame size doesn't match the GIF size"
+        );
+        self.frames.push((frame.data.borrow().clone(), delay));
+    }
+
+    /// Bounding rectangle of the pixels that differ between two frames.
+    fn changed_rect(&self, prev: &[u8], data: &[u8]) -> Option<[usize; 4]> {
+        let (mut min, mut max) = ((usize::MAX, usize::MAX), (0, 0));
+        for y in 0..self.height {
+            let row = y * self.width * 3..(y + 1) * self.width * 3;
+            let (a, b) = (&prev[row.clone()], &data[row]);
+            let Some(first) = (0..self.width).find(|x| a[x * 3..x * 3 + 3] != b[x * 3..x * 3 + 3])
+            else {
+                continue;
+            };
+            let last = (0..self.width)
+                .rev()
+                .find(|x| a[x * 3..x * 3 + 3] != b[x * 3..x * 3 + 3])
+                .unwrap_or(first);
+            min = (min.0.min(first), min.1.min(y));
+            max = (max.0.max(last), max.1.max(y));
+        }
+        if min.1 == usize::MAX {
+            return None;
+        }
+        // Aligned so ordered dithering lines up with the earlier frames.
+        let (x, y) = (min.0 / 8 * 8, min.1 / 8 * 8);
+        let (right, bottom) = (
+            (max.0 + 1).next_multiple_of(8).min(self.width),
+            (max.1 + 1).next_multiple_of(8).min(self.height),
+        );
+        Some([x, y, right - x, bottom - y])
+    }
+
+    /// Encodes the frames added so far.
+    pub fn encode(&self) -> Vec<u8> {
+        let histogram = |data: &[u8], histogram: &mut HashMap<[u8; 3], u32>| {
+            for c in data.chunks_exact(3) {
+                *histogram.entry([c[0], c[1], c[2]]).or_insert(0) += 1;
+            }
+        };
+        let global = match self.palette {
+            GifPalette::Global => {
+                let mut colors = HashMap::new();
+                for (data, _) in &self.frames {
+                    histogram(data, &mut colors);
+                }
+                Some(self.quantizer.palette(&colors, 256))
+            }
+            GifPalette::PerFrame => None,
+        };
+
+        let mut frames: Vec<Frame> = Vec::new();
+        let mut prev: Option<&[u8]> = None;
+        for (data, delay) in &self.frames {
+            let delay = (delay.as_millis() as f64 / 10.)
+                .round()
+                .min(u16::MAX as f64) as u16;
+            let rect = match prev {
+                None => Some([0, 0, self.width, self.height]),
+                Some(prev) => self.changed_rect(prev, data),
+            };
+            prev = Some(data);
+            let Some([x, y, w, h]) = rect else {
+                if let Some(last) = frames.last_mut() {
+                    last.delay = last.delay.saturating_add(delay);
+                }
+                continue;
+            };
+
+            let mut pixels = Vec::with_capacity(w * h * 3);
+            for row in y..y + h {
+                pixels.extend_from_slice(
+                    &data[(x + row * self.width) * 3..(x + w + row * self.width) * 3],
+                );
+            }
+            let local = global.is_none().then(|| {
+                let mut colors = HashMap::new();
+                histogram(&pixels, &mut colors);
+                self.quantizer.palette(&colors, 256)
+            });
+            let palette = local.as_deref().or(global.as_deref()).unwrap_or_default();
+            let indices = dither(&pixels, w, palette, self.dither)
+                .into_iter()
+                .map(|i| i as u8)
+                .collect();
+            frames.push(Frame {
+                rect: [x, y, w, h],
+                palette: local,
+                indices,
+                delay,
+            });
+        }
+
+        let mut out = b"GIF89a".to_vec();
+        out.extend_from_slice(&(self.width as u16).to_le_bytes());
+        out.extend_from_slice(&(self.height as u16).to_le_bytes());
+        // 8 bits per channel of color resolution, and the global table size if any.
+        match &global {
+            Some(palette) => {
+                out.extend_from_slice(&[0xf0 | (table_bits(palette) - 1), 0, 0]);
+                write_table(&mut out, palette);
+            }
+            None => out.extend_from_slice(&[0x70, 0, 0]),
+        }
+        if let Some(count) = self.loop_count {
+            out.extend_from_slice(b"\x21\xff\x0bNETSCAPE2.0\x03\x01");
+            out.extend_from_slice(&count.to_le_bytes());
+            out.push(0);
+        }
+
+        for frame in &frames {
+            // Graphic control: keep the previous frame under this one, no transparency.
+            out.extend_from_slice(&[0x21, 0xf9, 4, 1 << 2]);
+            out.extend_from_slice(&frame.delay.to_le_bytes());
+            out.extend_from_slice(&[0, 0]);
+
+            out.push(0x2c);
+            for v in frame.rect {
+                out.extend_from_slice(&(v as u16).to_le_bytes());
+            }
+            let bits = match &frame.palette {
+                Some(palette) => {
+                    out.push(0x80 | (table_bits(palette) - 1));
+                    write_table(&mut out, palette);
+                    table_bits(palette)
+                }
+                None => {
+                    out.push(0);
+                    global.as_deref().map_or(1, table_bits)
+                }
+            };
+            let min_size = bits.max(2);
+            out.push(min_size);
+            for block in lzw(&frame.indices, min_size).chunks(255) {
+                out.push(block.len() as u8);
+                out.extend_from_slice(block);
+            }
+            out.push(0);
+        }
+        out.push(0x3b);
+        out
+    }
+
+    /// Encodes the frames added so far and writes them to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        std::fs::write(path, self.encode())
+    }
+}
@@ -0,0 +1,112 @@
+use std::{error::Error, fmt};
+
+use crate::{color::Rgba, Buffer, Overlay};
+
+mod bmp;
+#[cfg(feature = "image-png")]
+mod deflate;
+mod gif;
+mod netpbm;
+#[cfg(feature = "image-png")]
+mod png;
+mod qoi;
+
+pub use gif::{GifEncoder, GifPalette};
+pub use netpbm::NetpbmFormat;
+pub use qoi::{QoiColorspace, QoiInfo};
+
+/// An image format that a [`Buffer`] can be encoded to.
+#[derive(Clone, Copy)]
+pub enum ImageFormat {
+    #[cfg(feature = "image-png")]
+    Png,
+    Netpbm(NetpbmFormat),
+    Bmp,
+    Qoi,
+}
+
+impl ImageFormat {
+    /// Get the format matching a file extension, ignoring case.
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        Some(match ext.to_ascii_lowercase().as_str() {
+            #[cfg(feature = "image-png")]
+            "png" => Self::Png,
+            "ppm" | "pnm" => Self::Netpbm(NetpbmFormat::Ppm),
+            "pgm" => Self::Netpbm(NetpbmFormat::Pgm),
+            "pam" => Self::Netpbm(NetpbmFormat::Pam),
+            "bmp" => Self::Bmp,
+            "qoi" => Self::Qoi,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ImageError {
+    /// The data isn't a valid image, with a short description of the problem.
+    Malformed(&'static str),
+    /// The image uses a feature that isn't supported.
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed(reason) => write!(f, "malformed image: {reason}"),
+            Self::Unsupported(feature) => write!(f, "unsupported image feature: {feature}"),
+        }
+    }
+}
+
+impl Error for ImageError {}
+
+impl Buffer {
+    /// Encodes the buffer in the given format.
+    pub fn encode(&self, format: ImageFormat) -> Vec<u8> {
+        match format {
+            #[cfg(feature = "image-png")]
+            ImageFormat::Png => self.to_png_bytes(),
+            ImageFormat::Netpbm(format) => self.to_netpbm(format),
+            ImageFormat::Bmp => self.to_bmp(),
+            ImageFormat::Qoi => self.encode_qoi(QoiColorspace::Srgb),
+        }
+    }
+}
+
+/// Blends a pixel with straight alpha over an opaque background.
+pub(crate) fn over_background([r, g, b, a]: [u8; 4], background: Rgba) -> [u8; 3] {
+    let mix =
+        |c: u8, bg: u8| ((c as u32 * a as u32 + bg as u32 * (255 - a as u32) + 127) / 255) as u8;
+    [
+        mix(r, background.r),
+        mix(g, background.g),
+        mix(b, background.b),
+    ]
+}
+
+/// Get the pixels of an overlay as stored, with premultiplied alpha.
+pub(crate) fn premultiplied_rgba(overlay: &Overlay) -> Vec<[u8; 4]> {
+    overlay
+        .overlay_data
+        .borrow()
+        .chunks_exact(4)
+        .map(|p| [p[0], p[1], p[2], p[3]])
+        .collect()
+}
+
+/// Get the pixels of an overlay, converted from premultiplied to straight alpha.
+pub(crate) fn straight_rgba(overlay: &Overlay) -> Vec<[u8; 4]> {
+    overlay
+        .overlay_data
+        .borrow()
+        .chunks_exact(4)
+        .map(|p| {
+            let a = p[3] as u16;
+            if a == 0 {
+                return [0; 4];
+            }
+            let c = |v: u8| ((v as u16 * 255 + a / 2) / a).min(255) as u8;
+            [c(p[0]), c(p[1]), c(p[2]), p[3]]
+        })
+        .collect()
+}
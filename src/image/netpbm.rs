@@ -0,0 +1,208 @@
+use super::{over_background, premultiplied_rgba, straight_rgba, ImageError};
+use crate::{color::Rgba, Buffer, Overlay};
+
+#[derive(Clone, Copy, Default)]
+pub enum NetpbmFormat {
+    /// Binary color, `P6`.
+    #[default]
+    Ppm,
+    /// ASCII color, `P3`.
+    PpmAscii,
+    /// Binary grayscale, `P5`.
+    Pgm,
+    /// `P7` with the `RGB` tuple type.
+    Pam,
+}
+
+/// Reads header tokens, skipping whitespace and comments.
+struct Header<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Header<'a> {
+    fn token(&mut self) -> Result<&'a str, ImageError> {
+        loop {
+            match self.data.get(self.pos) {
+                Some(b'#') => {
+                    while self.data.get(self.pos).is_some_and(|&c| c != b'\n') {
+                        self.pos += 1;
+                    }
+                }
+                Some(c) if c.is_ascii_whitespace() => self.pos += 1,
+                Some(_) => break,
+                None => return Err(ImageError::Malformed("truncated header")),
+            }
+        }
+        let start = self.pos;
+        while self
+            .data
+            .get(self.pos)
+            .is_some_and(|c| !c.is_ascii_whitespace())
+        {
+            self.pos += 1;
+        }
+        std::str::from_utf8(&self.data[start..self.pos])
+            .map_err(|_| ImageError::Malformed("invalid header"))
+    }
+
+    fn number(&mut self) -> Result<usize, ImageError> {
+        self.token()?
+            .parse()
+            .map_err(|_| ImageError::Malformed("invalid number in header"))
+    }
+}
+
+impl Buffer {
+    /// Encodes the buffer as a Netpbm image. Grayscale uses the intensity of each pixel.
+    pub fn to_netpbm(&self, format: NetpbmFormat) -> Vec<u8> {
+        let data = self.data.borrow();
+        let (w, h) = (self.width, self.height);
+        match format {
+            NetpbmFormat::Ppm => {
+                let mut out = format!("P6\n{w} {h}\n255\n").into_bytes();
+                out.extend_from_slice(&data);
+                out
+            }
+            NetpbmFormat::PpmAscii => {
+                let mut out = format!("P3\n{w} {h}\n255\n");
+                for row in data.chunks((w * 3).max(1)).take(h) {
+                    let row: Vec<_> = row.iter().map(|c| c.to_string()).collect();
+                    // Lines should stay under 70 characters.
+                    for line in row.chunks(15) {
+                        out += &line.join(" ");
+                        out.push('\n');
+                    }
+                }
+                out.into_bytes()
+            }
+            NetpbmFormat::Pgm => {
+                let mut out = format!("P5\n{w} {h}\n255\n").into_bytes();
+                out.extend(
+                    data.chunks_exact(3)
+                        .map(|c| Rgba::from([c[0], c[1], c[2]]).intensity()),
+                );
+                out
+            }
+            NetpbmFormat::Pam => {
+                let mut out = format!(
+                    "P7\nWIDTH {w}\nHEIGHT {h}\nDEPTH 3\nMAXVAL 255\nTUPLTYPE RGB\nENDHDR\n"
+                )
+                .into_bytes();
+                out.extend_from_slice(&data);
+                out
+            }
+        }
+    }
+
+    /// Decodes a `P2`, `P3`, `P5`, `P6` or `P7` Netpbm image, blending transparent pixels over white.
+    pub fn from_netpbm(data: &[u8]) -> Result<Self, ImageError> {
+        Self::from_netpbm_with_background(data, Rgba::WHITE)
+    }
+
+    /// Decodes a `P2`, `P3`, `P5`, `P6` or `P7` Netpbm image, blending transparent pixels over `background`.
+    pub fn from_netpbm_with_background(data: &[u8], background: Rgba) -> Result<Self, ImageError> {
+        let mut header = Header { data, pos: 0 };
+        let magic = header.token()?;
+        let (width, height, depth, maxval) = if magic == "P7" {
+            let (mut width, mut height, mut depth, mut maxval) = (None, None, None, None);
+            loop {
+                match header.token()? {
+                    "WIDTH" => width = Some(header.number()?),
+                    "HEIGHT" => height = Some(header.number()?),
+                    "DEPTH" => depth = Some(header.number()?),
+                    "MAXVAL" => maxval = Some(header.number()?),
+                    // The depth tells the layout already.
+                    "TUPLTYPE" => {
+                        header.token()?;
+                    }
+                    "ENDHDR" => break,
+                    _ => return Err(ImageError::Malformed("unknown PAM header field")),
+                }
+            }
+            match (width, height, depth, maxval) {
+                (Some(w), Some(h), Some(d @ 1..=4), Some(m)) => (w, h, d, m),
+                _ => return Err(ImageError::Malformed("incomplete PAM header")),
+            }
+        } else {
+            let depth = match magic {
+                "P2" | "P5" => 1,
+                "P3" | "P6" => 3,
+                _ => return Err(ImageError::Unsupported("Netpbm format")),
+            };
+            (header.number()?, header.number()?, depth, header.number()?)
+        };
+        if maxval == 0 || maxval > 65535 {
+            return Err(ImageError::Malformed("invalid maximum value"));
+        }
+
+        let malformed = ImageError::Malformed;
+        let count = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(depth))
+            .ok_or(malformed("image size too large"))?;
+        let samples: Vec<u32> = if magic == "P2" || magic == "P3" {
+            // Each sample takes at least one digit.
+            if count > data.len() - header.pos {
+                return Err(malformed("truncated image data"));
+            }
+            (0..count)
+                .map(|_| header.number().map(|v| v as u32))
+                .collect::<Result<_, _>>()?
+        } else {
+            // A single whitespace separates the header from the data.
+            let start = header.pos + 1;
+            let size = if maxval > 255 { 2 } else { 1 };
+            let body = count
+                .checked_mul(size)
+                .and_then(|len| data.get(start..start.checked_add(len)?))
+                .ok_or(malformed("truncated image data"))?;
+            if size == 2 {
+                body.chunks_exact(2)
+                    .map(|c| u16::from_be_bytes([c[0], c[1]]) as u32)
+                    .collect()
+            } else {
+                body.iter().map(|&c| c as u32).collect()
+            }
+        };
+
+        let buf = Buffer::new(width, height);
+        let mut out = buf.data.borrow_mut();
+        let scale =
+            |v: u32| ((v.min(maxval as u32) * 255 + maxval as u32 / 2) / maxval as u32) as u8;
+        for (i, pixel) in samples.chunks_exact(depth).enumerate() {
+            let p = pixel.iter().map(|&v| scale(v)).collect::<Vec<_>>();
+            let rgba = match *p {
+                [g] => [g, g, g, 255],
+                [g, a] => [g, g, g, a],
+                [r, g, b] => [r, g, b, 255],
+                [r, g, b, a] => [r, g, b, a],
+                _ => unreachable!(),
+            };
+            out[i * 3..i * 3 + 3].copy_from_slice(&over_background(rgba, background));
+        }
+        drop(out);
+        Ok(buf)
+    }
+}
+
+impl Overlay {
+    /// Encodes the overlay as a `P7` PAM image with the `RGB_ALPHA` tuple type, converting its pixels to straight alpha.
+    pub fn to_pam(&self) -> Vec<u8> {
+        self.to_pam_rgba(&straight_rgba(self))
+    }
+
+    /// Encodes the overlay as a `P7` PAM image with the `RGB_ALPHA` tuple type, keeping its pixels premultiplied as stored.
+    pub fn to_pam_premultiplied(&self) -> Vec<u8> {
+        self.to_pam_rgba(&premultiplied_rgba(self))
+    }
+
+    fn to_pam_rgba(&self, pixels: &[[u8; 4]]) -> Vec<u8> {
+        let (w, h) = (self.dst_rect.w, self.dst_rect.h);
+        let mut out =
+            format!("P7\nWIDTH {w}\nHEIGHT {h}\nDEPTH 4\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n")
+                .into_bytes();
+        out.extend(pixels.iter().flatten());
+        out
+    }
+}
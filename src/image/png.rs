@@ -0,0 +1,313 @@
+use std::{io, path::Path};
+
+use super::{
+    deflate::{zlib_compress, zlib_decompress},
+    over_background, ImageError,
+};
+use crate::{color::Rgba, Buffer};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+fn crc32(data: &[&[u8]]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data.iter().copied().flatten() {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xedb88320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+fn write_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    out.extend_from_slice(&crc32(&[kind, data]).to_be_bytes());
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let (pa, pb, pc) = (
+        (p - a as i16).abs(),
+        (p - b as i16).abs(),
+        (p - c as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
+}
+
+/// Reverses the filter of a row in place, given the unfiltered previous row.
+fn unfilter(filter: u8, row: &mut [u8], prev: &[u8], bpp: usize) -> Result<(), ImageError> {
+    for i in 0..row.len() {
+        let a = if i >= bpp { row[i - bpp] } else { 0 };
+        let b = prev[i];
+        let c = if i >= bpp { prev[i - bpp] } else { 0 };
+        row[i] = row[i].wrapping_add(match filter {
+            0 => 0,
+            1 => a,
+            2 => b,
+            3 => ((a as u16 + b as u16) / 2) as u8,
+            4 => paeth(a, b, c),
+            _ => return Err(ImageError::Malformed("invalid PNG filter")),
+        });
+    }
+    Ok(())
+}
+
+/// Adam7 passes as (x start, y start, x step, y step).
+const ADAM7: [(usize, usize, usize, usize); 7] = [
+    (0, 0, 8, 8),
+    (4, 0, 8, 8),
+    (0, 4, 4, 8),
+    (2, 0, 4, 4),
+    (0, 2, 2, 4),
+    (1, 0, 2, 2),
+    (0, 1, 1, 2),
+];
+
+impl Buffer {
+    /// Encodes the buffer as an 8-bit RGB PNG.
+    pub fn to_png_bytes(&self) -> Vec<u8> {
+        let data = self.data.borrow();
+        let stride = self.width * 3;
+        let mut raw = Vec::with_capacity((stride + 1) * self.height);
+        let mut filtered = vec![0; stride];
+        let zero = vec![0; stride];
+        for y in 0..self.height {
+            let row = &data[y * stride..(y + 1) * stride];
+            let prev = if y == 0 {
+                &zero[..]
+            } else {
+                &data[(y - 1) * stride..y * stride]
+            };
+            // Pick the filter with the smallest sum of absolute values.
+            let mut best = (u64::MAX, 0, Vec::new());
+            for filter in 0..5 {
+                for i in 0..stride {
+                    let a = if i >= 3 { row[i - 3] } else { 0 };
+                    let c = if i >= 3 { prev[i - 3] } else { 0 };
+                    filtered[i] = row[i].wrapping_sub(match filter {
+                        0 => 0,
+                        1 => a,
+                        2 => prev[i],
+                        3 => ((a as u16 + prev[i] as u16) / 2) as u8,
+                        _ => paeth(a, prev[i], c),
+                    });
+                }
+                let score = filtered
+                    .iter()
+                    .map(|&v| (v as i8).unsigned_abs() as u64)
+                    .sum();
+                if score < best.0 {
+                    best = (score, filter, filtered.clone());
+                }
+            }
+            raw.push(best.1);
+            raw.extend_from_slice(&best.2);
+        }
+
+        let mut out = SIGNATURE.to_vec();
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&(self.width as u32).to_be_bytes());
+        header.extend_from_slice(&(self.height as u32).to_be_bytes());
+        // 8 bits per channel, RGB, deflate, adaptive filtering, no interlacing.
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+        write_chunk(&mut out, b"IHDR", &header);
+        write_chunk(&mut out, b"IDAT", &zlib_compress(&raw));
+        write_chunk(&mut out, b"IEND", &[]);
+        out
+    }
+
+    /// Saves the buffer as a PNG file.
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        std::fs::write(path, self.to_png_bytes())
+    }
+
+    /// Decodes a PNG, blending transparent pixels over white.
+    pub fn from_png(data: &[u8]) -> Result<Self, ImageError> {
+        Self::from_png_with_background(data, Rgba::WHITE)
+    }
+
+    /// Decodes a PNG, blending transparent pixels over `background`.
+    pub fn from_png_with_background(data: &[u8], background: Rgba) -> Result<Self, ImageError> {
+        let malformed = |reason| ImageError::Malformed(reason);
+        if data.get(..8) != Some(&SIGNATURE[..]) {
+            return Err(malformed("not a PNG file"));
+        }
+
+        let mut header = None;
+        let mut palette: Vec<[u8; 4]> = Vec::new();
+        let mut transparent = None;
+        let mut compressed = Vec::new();
+        let mut pos = 8;
+        loop {
+            let len = data.get(pos..pos + 4).ok_or(malformed("truncated chunk"))?;
+            let len = u32::from_be_bytes([len[0], len[1], len[2], len[3]]) as usize;
+            let kind = data
+                .get(pos + 4..pos + 8)
+                .ok_or(malformed("truncated chunk"))?;
+            let body = data
+                .get(pos + 8..pos + 8 + len)
+                .ok_or(malformed("truncated chunk"))?;
+            let crc = data
+                .get(pos + 8 + len..pos + 12 + len)
+                .ok_or(malformed("truncated chunk"))?;
+            if crc32(&[kind, body]).to_be_bytes() != crc {
+                return Err(malformed("chunk checksum mismatch"));
+            }
+            pos += 12 + len;
+            match kind {
+                b"IHDR" if body.len() == 13 => header = Some(body),
+                b"PLTE" => {
+                    palette = body
+                        .chunks_exact(3)
+                        .map(|c| [c[0], c[1], c[2], 255])
+                        .collect()
+                }
+                b"tRNS" => transparent = Some(body),
+                b"IDAT" => compressed.extend_from_slice(body),
+                b"IEND" => break,
+                _ if kind[0] & 0x20 == 0 => return Err(ImageError::Unsupported("critical chunk")),
+                _ => {}
+            }
+        }
+
+        let header = header.ok_or(malformed("missing header"))?;
+        let width = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let height = u32::from_be_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        let (depth, color_type, interlaced) = (header[8] as usize, header[9], header[12] == 1);
+        let channels = match (color_type, depth) {
+            (0, 1 | 2 | 4 | 8 | 16) => 1,
+            (3, 1 | 2 | 4 | 8) => 1,
+            (2 | 6, 8 | 16) | (4, 8 | 16) => [0, 0, 3, 0, 2, 0, 4][color_type as usize],
+            _ => return Err(ImageError::Unsupported("bit depth and color type")),
+        };
+        if header[10] != 0 || header[11] != 0 || header[12] > 1 {
+            return Err(ImageError::Unsupported(
+                "compression, filter or interlace method",
+            ));
+        }
+        match (color_type, transparent) {
+            (3, Some(alpha)) => {
+                for (entry, &a) in palette.iter_mut().zip(alpha) {
+                    entry[3] = a;
+                }
+            }
+            (3, None) | (_, None) => {}
+            (_, Some(key)) if key.len() < 2 * channels => return Err(malformed("invalid tRNS")),
+            _ => {}
+        }
+
+        let bits = depth * channels;
+        let bpp = bits.div_ceil(8);
+        let passes: &[_] = if interlaced { &ADAM7 } else { &[(0, 0, 1, 1)] };
+        let pass_size = |&(x0, y0, dx, dy): &(usize, usize, usize, usize)| {
+            let (pass_w, pass_h) = (
+                width.saturating_sub(x0).div_ceil(dx),
+                height.saturating_sub(y0).div_ceil(dy),
+            );
+            if pass_w == 0 || pass_h == 0 {
+                return Some(0);
+            }
+            // Each row starts with its filter type.
+            let stride = pass_w.checked_mul(bits)?.div_ceil(8);
+            pass_h.checked_mul(stride.checked_add(1)?)
+        };
+        let expected = passes
+            .iter()
+            .try_fold(0usize, |total, pass| total.checked_add(pass_size(pass)?))
+            .filter(|_| {
+                width
+                    .checked_mul(height)
+                    .and_then(|n| n.checked_mul(3))
+                    .is_some()
+            })
+            .ok_or(malformed("image size too large"))?;
+        let raw = zlib_decompress(&compressed, expected)?;
+        if raw.len() != expected {
+            return Err(malformed("truncated image data"));
+        }
+        let buf = Buffer::new(width, height);
+        let mut out = buf.data.borrow_mut();
+
+        // Converts a sample at `index` of a row to a pixel.
+        let sample = |row: &[u8], index: usize, channel: usize| -> u16 {
+            if depth == 16 {
+                let i = (index * channels + channel) * 2;
+                u16::from_be_bytes([row[i], row[i + 1]])
+            } else {
+                let bit = (index * channels + channel) * depth;
+                (row[bit / 8] >> (8 - depth - bit % 8)) as u16 & ((1 << depth) - 1)
+            }
+        };
+        let to_u8 = |v: u16| match depth {
+            16 => ((v as u32 * 255 + 32767) / 65535) as u8,
+            _ => (v as u32 * 255 / ((1 << depth) - 1)) as u8,
+        };
+        let key = |i: usize| transparent.map(|t| u16::from_be_bytes([t[2 * i], t[2 * i + 1]]));
+        let pixel = |row: &[u8], index: usize| -> [u8; 4] {
+            let s = |c| sample(row, index, c);
+            match color_type {
+                0 => {
+                    let g = s(0);
+                    let a = if key(0) == Some(g) { 0 } else { 255 };
+                    let g = to_u8(g);
+                    [g, g, g, a]
+                }
+                2 => {
+                    let (r, g, b) = (s(0), s(1), s(2));
+                    let a = match transparent {
+                        Some(_) if (key(0), key(1), key(2)) == (Some(r), Some(g), Some(b)) => 0,
+                        _ => 255,
+                    };
+                    [to_u8(r), to_u8(g), to_u8(b), a]
+                }
+                3 => palette
+                    .get(s(0) as usize)
+                    .copied()
+                    .unwrap_or([0, 0, 0, 255]),
+                4 => {
+                    let g = to_u8(s(0));
+                    [g, g, g, to_u8(s(1))]
+                }
+                _ => [to_u8(s(0)), to_u8(s(1)), to_u8(s(2)), to_u8(s(3))],
+            }
+        };
+
+        let mut pos = 0;
+        for &(x0, y0, dx, dy) in passes {
+            let (pass_w, pass_h) = (
+                width.saturating_sub(x0).div_ceil(dx),
+                height.saturating_sub(y0).div_ceil(dy),
+            );
+            if pass_w == 0 || pass_h == 0 {
+                continue;
+            }
+            let stride = (pass_w * bits).div_ceil(8);
+            let mut prev = vec![0; stride];
+            for j in 0..pass_h {
+                let filter = *raw.get(pos).ok_or(malformed("truncated image data"))?;
+                let row = raw
+                    .get(pos + 1..pos + 1 + stride)
+                    .ok_or(malformed("truncated image data"))?;
+                pos += 1 + stride;
+                let mut row = row.to_vec();
+                unfilter(filter, &mut row, &prev, bpp)?;
+                for i in 0..pass_w {
+                    let (x, y) = (x0 + i * dx, y0 + j * dy);
+                    let o = (x + y * width) * 3;
+                    out[o..o + 3].copy_from_slice(&over_background(pixel(&row, i), background));
+                }
+                prev = row;
+            }
+        }
+        drop(out);
+        Ok(buf)
+    }
+}
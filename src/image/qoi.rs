@@ -0,0 +1,236 @@
+use super::{over_background, straight_rgba, ImageError};
+use crate::{color::Rgba, Buffer, Offset, Overlay, Rect, Size};
+
+const OP_INDEX: u8 = 0x00;
+const OP_DIFF: u8 = 0x40;
+const OP_LUMA: u8 = 0x80;
+const OP_RUN: u8 = 0xc0;
+const OP_RGB: u8 = 0xfe;
+const OP_RGBA: u8 = 0xff;
+const END: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 1];
+
+/// Colorspace tag stored in a QOI header. It doesn't change how pixels are encoded.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum QoiColorspace {
+    /// sRGB color with linear alpha.
+    #[default]
+    Srgb,
+    /// All channels linear.
+    Linear,
+}
+
+/// Header fields of a decoded QOI image besides its size.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct QoiInfo {
+    /// 3 for RGB, 4 for RGBA.
+    pub channels: u8,
+    pub colorspace: QoiColorspace,
+}
+
+fn hash([r, g, b, a]: [u8; 4]) -> usize {
+    (r as usize * 3 + g as usize * 5 + b as usize * 7 + a as usize * 11) % 64
+}
+
+fn encode(
+    pixels: impl Iterator<Item = [u8; 4]>,
+    size: (usize, usize),
+    channels: u8,
+    colorspace: QoiColorspace,
+) -> Vec<u8> {
+    let mut out = Vec::with_capacity(14 + size.0 * size.1 * (channels as usize + 1) + 8);
+    out.extend_from_slice(b"qoif");
+    out.extend_from_slice(&(size.0 as u32).to_be_bytes());
+    out.extend_from_slice(&(size.1 as u32).to_be_bytes());
+    out.push(channels);
+    out.push(colorspace as u8);
+
+    let mut index = [[0u8; 4]; 64];
+    let mut prev = [0, 0, 0, 255];
+    let mut run = 0;
+    for px in pixels {
+        if px == prev {
+            run += 1;
+            if run == 62 {
+                out.push(OP_RUN | (run - 1));
+                run = 0;
+            }
+            continue;
+        }
+        if run > 0 {
+            out.push(OP_RUN | (run - 1));
+            run = 0;
+        }
+        let pos = hash(px);
+        if index[pos] == px {
+            out.push(OP_INDEX | pos as u8);
+        } else {
+            index[pos] = px;
+            if px[3] == prev[3] {
+                let d = |i: usize| px[i].wrapping_sub(prev[i]) as i8;
+                let (dr, dg, db) = (d(0), d(1), d(2));
+                let (dr_dg, db_dg) = (dr.wrapping_sub(dg), db.wrapping_sub(dg));
+                if (-2..2).contains(&dr) && (-2..2).contains(&dg) && (-2..2).contains(&db) {
+                    out.push(
+                        OP_DIFF | ((dr + 2) as u8) << 4 | ((dg + 2) as u8) << 2 | (db + 2) as u8,
+                    );
+                } else if (-32..32).contains(&dg)
+                    && (-8..8).contains(&dr_dg)
+                    && (-8..8).contains(&db_dg)
+                {
+                    out.push(OP_LUMA | (dg + 32) as u8);
+                    out.push(((dr_dg + 8) as u8) << 4 | (db_dg + 8) as u8);
+                } else {
+                    out.extend_from_slice(&[OP_RGB, px[0], px[1], px[2]]);
+                }
+            } else {
+                out.extend_from_slice(&[OP_RGBA, px[0], px[1], px[2], px[3]]);
+            }
+        }
+        prev = px;
+    }
+    if run > 0 {
+        out.push(OP_RUN | (run - 1));
+    }
+    out.extend_from_slice(&END);
+    out
+}
+
+/// Reads the size and header fields of a QOI image.
+fn header(data: &[u8]) -> Result<(usize, usize, QoiInfo), ImageError> {
+    let malformed = ImageError::Malformed;
+    if data.len() < 14 || &data[..4] != b"qoif" {
+        return Err(malformed("not a QOI file"));
+    }
+    let width = u32::from_be_bytes([data[4], data[5], data[6], data[7]]) as usize;
+    let height = u32::from_be_bytes([data[8], data[9], data[10], data[11]]) as usize;
+    let colorspace = match data[13] {
+        0 => QoiColorspace::Srgb,
+        1 => QoiColorspace::Linear,
+        _ => return Err(malformed("invalid header")),
+    };
+    if !(3..=4).contains(&data[12]) {
+        return Err(malformed("invalid header"));
+    }
+    // Each byte can hold at most a run of 62 pixels.
+    width
+        .checked_mul(height)
+        .filter(|&count| count <= data.len().saturating_mul(62))
+        .ok_or(malformed("image size too large for the data"))?;
+    let info = QoiInfo {
+        channels: data[12],
+        colorspace,
+    };
+    Ok((width, height, info))
+}
+
+/// Decodes `count` pixels after the header, calling `put` with the index and straight RGBA value of each.
+fn decode(
+    data: &[u8],
+    count: usize,
+    mut put: impl FnMut(usize, [u8; 4]),
+) -> Result<(), ImageError> {
+    let mut index = [[0u8; 4]; 64];
+    let mut px = [0, 0, 0, 255];
+    let (mut pos, mut i) = (0, 14);
+    let mut byte = || {
+        let b = data
+            .get(i)
+            .copied()
+            .ok_or(ImageError::Malformed("truncated image data"));
+        i += 1;
+        b
+    };
+    while pos < count {
+        let op = byte()?;
+        let mut run = 1;
+        match op {
+            OP_RGB => px = [byte()?, byte()?, byte()?, px[3]],
+            OP_RGBA => px = [byte()?, byte()?, byte()?, byte()?],
+            _ => match op & 0xc0 {
+                OP_INDEX => px = index[op as usize],
+                OP_DIFF => {
+                    let d = |shift: u8| ((op >> shift) & 3).wrapping_sub(2);
+                    px[0] = px[0].wrapping_add(d(4));
+                    px[1] = px[1].wrapping_add(d(2));
+                    px[2] = px[2].wrapping_add(d(0));
+                }
+                OP_LUMA => {
+                    let next = byte()?;
+                    let dg = (op & 0x3f).wrapping_sub(32);
+                    px[0] = px[0].wrapping_add(dg.wrapping_add(next >> 4).wrapping_sub(8));
+                    px[1] = px[1].wrapping_add(dg);
+                    px[2] = px[2].wrapping_add(dg.wrapping_add(next & 0xf).wrapping_sub(8));
+                }
+                _ => run = (op & 0x3f) as usize + 1,
+            },
+        }
+        index[hash(px)] = px;
+        for _ in 0..run.min(count - pos) {
+            put(pos, px);
+            pos += 1;
+        }
+    }
+    Ok(())
+}
+
+impl Buffer {
+    /// Encodes the buffer as a 3 channel QOI image.
+    pub fn encode_qoi(&self, colorspace: QoiColorspace) -> Vec<u8> {
+        let data = self.data.borrow();
+        let pixels = data.chunks_exact(3).map(|c| [c[0], c[1], c[2], 255]);
+        encode(pixels, (self.width, self.height), 3, colorspace)
+    }
+
+    /// Decodes a QOI image, blending transparent pixels over white.
+    pub fn decode_qoi(data: &[u8]) -> Result<(Self, QoiInfo), ImageError> {
+        Self::decode_qoi_with_background(data, Rgba::WHITE)
+    }
+
+    /// Decodes a QOI image, blending transparent pixels over `background`.
+    ///
+    /// Use [`Overlay::decode_qoi`] to keep the alpha of 4 channel images.
+    pub fn decode_qoi_with_background(
+        data: &[u8],
+        background: Rgba,
+    ) -> Result<(Self, QoiInfo), ImageError> {
+        let (width, height, info) = header(data)?;
+        let buf = Buffer::new(width, height);
+        let mut out = buf.data.borrow_mut();
+        decode(data, width * height, |i, px| {
+            out[i * 3..i * 3 + 3].copy_from_slice(&over_background(px, background));
+        })?;
+        drop(out);
+        Ok((buf, info))
+    }
+}
+
+impl Overlay {
+    /// Encodes the overlay as a 4 channel QOI image, with straight alpha.
+    pub fn encode_qoi(&self, colorspace: QoiColorspace) -> Vec<u8> {
+        let size = (self.dst_rect.w as usize, self.dst_rect.h as usize);
+        encode(straight_rgba(self).into_iter(), size, 4, colorspace)
+    }
+
+    /// Decodes a QOI image into an overlay of `base` at `offset`, keeping its alpha.
+    pub fn decode_qoi(
+        base: Buffer,
+        offset: Offset,
+        data: &[u8],
+    ) -> Result<(Self, QoiInfo), ImageError> {
+        let (width, height, info) = header(data)?;
+        let rect = Rect::new(offset, Size::new(width as u32, height as u32));
+        let overlay = Overlay::new(base, rect);
+        let mut out = overlay.overlay_data.borrow_mut();
+        decode(data, width * height, |i, [r, g, b, a]| {
+            let premultiply = |c: u8| ((c as u16 * a as u16 + 127) / 255) as u8;
+            out[i * 4..i * 4 + 4].copy_from_slice(&[
+                premultiply(r),
+                premultiply(g),
+                premultiply(b),
+                a,
+            ]);
+        })?;
+        drop(out);
+        Ok((overlay, info))
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{blend::BlendMode, Buffer, Overlay, Rect};
+
+/// Identifies a layer in a [`LayerStack`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct LayerId(u32);
+
+pub struct Layer {
+    pub overlay: Overlay,
+    /// Layers with higher z are drawn on top. Equal ones keep the order they were added in.
+    pub z: i32,
+    pub visible: bool,
+    pub opacity: u8,
+    pub blend_mode: BlendMode,
+}
+
+/// Overlays drawn over a background, that can be recomposited after being changed, moved or removed.
+pub struct LayerStack {
+    base: Buffer,
+    background: Buffer,
+    layers: Vec<(LayerId, Layer)>,
+    next_id: u32,
+}
+
+impl LayerStack {
+    /// The current contents of `base` become the background.
+    pub fn new(base: Buffer) -> Self {
+        let background = Buffer::new(base.width, base.height);
+        background
+            .data
+            .borrow_mut()
+            .copy_from_slice(&base.data.borrow());
+        Self {
+            base,
+            background,
+            layers: Vec::new(),
+            next_id: 0,
+        }
+    }
+
+    /// The buffer under all layers, which can be drawn to.
+    pub fn background(&self) -> &Buffer {
+        &self.background
+    }
+
+    /// Adds a visible layer at full opacity covering `rect` of the base buffer. Its overlay starts fully transparent.
+    pub fn add(&mut self, rect: Rect, z: i32) -> LayerId {
+        let id = LayerId(self.next_id);
+        self.next_id += 1;
+        let layer = Layer {
+            overlay: Overlay::new(self.base.clone(), rect),
+            z,
+            visible: true,
+            opacity: 255,
+            blend_mode: BlendMode::Normal,
+        };
+        self.layers.push((id, layer));
+        id
+    }
+
+    pub fn remove(&mut self, id: LayerId) -> Option<Layer> {
+        let i = self.layers.iter().position(|(i, _)| *i == id)?;
+        Some(self.layers.remove(i).1)
+    }
+
+    pub fn get(&self, id: LayerId) -> Option<&Layer> {
+        self.layers.iter().find(|(i, _)| *i == id).map(|(_, l)| l)
+    }
+
+    pub fn get_mut(&mut self, id: LayerId) -> Option<&mut Layer> {
+        self.layers
+            .iter_mut()
+            .find(|(i, _)| *i == id)
+            .map(|(_, l)| l)
+    }
+
+    /// Redraws the base buffer from the background and visible layers, and returns it.
+    pub fn composite(&self) -> Buffer {
+        self.base
+            .data
+            .borrow_mut()
+            .copy_from_slice(&self.background.data.borrow());
+        let mut layers: Vec<_> = self
+            .layers
+            .iter()
+            .map(|(_, l)| l)
+            .filter(|l| l.visible && l.opacity > 0)
+            .collect();
+        layers.sort_by_key(|l| l.z);
+        for layer in layers {
+            layer.overlay.write_blended(layer.blend_mode, layer.opacity);
+        }
+        self.base.clone()
+    }
+}
@@ -1,8 +1,16 @@
-use std::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+use core::f32::consts::{FRAC_PI_2 as PI_2_32, TAU as TAU32};
+use std::{
+    mem,
+    ops::{Add, AddAssign, Neg, Sub, SubAssign},
+};
 
+pub mod blend;
 pub mod color;
+pub mod dither;
 pub mod draw;
-#[cfg(feature = "window")]
+pub mod image;
+pub mod layer;
+pub mod text;
 pub mod window;
 
 pub use draw::{Buffer, Drawable, Overlay};
@@ -115,6 +123,678 @@ impl Size {
     }
 }
 
+/// Maximum distance in pixels between a curve and its flattened polyline.
+const TOLERANCE: f32 = 0.1;
+
+/// Shortest dash pattern, in pixels, below which strokes are drawn solid.
+const MIN_DASH_PERIOD: f32 = 0.1;
+
+/// Most dashes along one polyline before it's drawn solid instead.
+const MAX_DASHES: f32 = 1e6;
+
+#[derive(Clone, Copy)]
+pub enum PathSegment {
+    MoveTo(f32, f32),
+    LineTo(f32, f32),
+    /// Control point, end point.
+    QuadTo(f32, f32, f32, f32),
+    /// First control point, second control point, end point.
+    CubicTo(f32, f32, f32, f32, f32, f32),
+    Close,
+}
+
+/// A vector path made of lines and Bézier curves, in pixel coordinates.
+///
+/// Integer coordinates lie on pixel corners, like [`Rect`] borders, so one pixel wide lines are sharpest through pixel centers (`x + 0.5`).
+#[derive(Clone, Default)]
+pub struct Path {
+    segments: Vec<PathSegment>,
+    start: (f32, f32),
+    current: Option<(f32, f32)>,
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn segments(&self) -> &[PathSegment] {
+        &self.segments
+    }
+    /// Starts a new subpath.
+    pub fn move_to(&mut self, x: f32, y: f32) -> &mut Self {
+        self.segments.push(PathSegment::MoveTo(x, y));
+        self.start = (x, y);
+        self.current = Some((x, y));
+        self
+    }
+    /// Adds a line. Starts a new subpath instead if there's none.
+    pub fn line_to(&mut self, x: f32, y: f32) -> &mut Self {
+        if self.current.is_none() {
+            return self.move_to(x, y);
+        }
+        self.segments.push(PathSegment::LineTo(x, y));
+        self.current = Some((x, y));
+        self
+    }
+    /// Adds a quadratic Bézier curve with control point `(x1, y1)`.
+    pub fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) -> &mut Self {
+        if self.current.is_none() {
+            self.move_to(x1, y1);
+        }
+        self.segments.push(PathSegment::QuadTo(x1, y1, x, y));
+        self.current = Some((x, y));
+        self
+    }
+    /// Adds a cubic Bézier curve with control points `(x1, y1)` and `(x2, y2)`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn cubic_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) -> &mut Self {
+        if self.current.is_none() {
+            self.move_to(x1, y1);
+        }
+        self.segments
+            .push(PathSegment::CubicTo(x1, y1, x2, y2, x, y));
+        self.current = Some((x, y));
+        self
+    }
+    /// Adds an elliptical arc to `(x, y)`, with the same parameters as SVG's `A` command.
+    /// `x_rotation` is in radians, radii too small to reach the end point are scaled up.
+    #[allow(clippy::too_many_arguments)]
+    pub fn arc_to(
+        &mut self,
+        rx: f32,
+        ry: f32,
+        x_rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+        x: f32,
+        y: f32,
+    ) -> &mut Self {
+        let Some((x1, y1)) = self.current else {
+            return self.move_to(x, y);
+        };
+        let (mut rx, mut ry) = (rx.abs(), ry.abs());
+        if (x1 == x && y1 == y) || rx == 0. || ry == 0. {
+            return self.line_to(x, y);
+        }
+        // Endpoint to center parameterization, see SVG 1.1 appendix F.6.5.
+        let (sin, cos) = x_rotation.sin_cos();
+        let (hx, hy) = ((x1 - x) / 2., (y1 - y) / 2.);
+        let (x1p, y1p) = (cos * hx + sin * hy, -sin * hx + cos * hy);
+        let lambda = (x1p / rx).powi(2) + (y1p / ry).powi(2);
+        if lambda > 1. {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+        let num = rx * rx * ry * ry - rx * rx * y1p * y1p - ry * ry * x1p * x1p;
+        let den = rx * rx * y1p * y1p + ry * ry * x1p * x1p;
+        let coef = (num / den).max(0.).sqrt() * if large_arc == sweep { -1. } else { 1. };
+        let (cxp, cyp) = (coef * rx * y1p / ry, -coef * ry * x1p / rx);
+        let cx = cos * cxp - sin * cyp + (x1 + x) / 2.;
+        let cy = sin * cxp + cos * cyp + (y1 + y) / 2.;
+
+        let angle = |ux: f32, uy: f32| uy.atan2(ux);
+        let t1 = angle((x1p - cxp) / rx, (y1p - cyp) / ry);
+        let mut dt = angle((-x1p - cxp) / rx, (-y1p - cyp) / ry) - t1;
+        if sweep && dt < 0. {
+            dt += TAU32;
+        } else if !sweep && dt > 0. {
+            dt -= TAU32;
+        }
+        self.ellipse_segment((cx, cy), (rx, ry), x_rotation, t1, dt);
+        // Avoid rounding errors in the end point.
+        self.current = Some((x, y));
+        self
+    }
+    /// Adds an elliptical arc from angle `start` sweeping `sweep` radians, with positive angles measured counterclockwise from positive x axis.
+    /// Angles are those of the ellipse's parametric form, which only match the polar angle of points on circles.
+    /// Connects to the start of the arc with a line if there's a current point.
+    pub fn arc(&mut self, cx: f32, cy: f32, rx: f32, ry: f32, start: f32, sweep: f32) -> &mut Self {
+        let (x, y) = (cx + rx * start.cos(), cy - ry * start.sin());
+        self.line_to(x, y);
+        // The y axis points down, so the parameter goes the other way.
+        self.ellipse_segment((cx, cy), (rx, ry), 0., -start, -sweep);
+        self
+    }
+    /// Closes the current subpath with a line to its start.
+    pub fn close(&mut self) -> &mut Self {
+        if self.current.is_some() {
+            self.segments.push(PathSegment::Close);
+            self.current = Some(self.start);
+        }
+        self
+    }
+
+    /// Appends cubic curves following `center + R(rotation) * (rx * cos(t), ry * sin(t))` for `t` in `t1..t1 + dt`.
+    fn ellipse_segment(
+        &mut self,
+        (cx, cy): (f32, f32),
+        (rx, ry): (f32, f32),
+        rotation: f32,
+        t1: f32,
+        dt: f32,
+    ) {
+        let (sin, cos) = rotation.sin_cos();
+        let point = |t: f32| {
+            let (x, y) = (rx * t.cos(), ry * t.sin());
+            (cx + x * cos - y * sin, cy + x * sin + y * cos)
+        };
+        let deriv = |t: f32| {
+            let (x, y) = (-rx * t.sin(), ry * t.cos());
+            (x * cos - y * sin, x * sin + y * cos)
+        };
+        // At most a quarter turn per curve keeps the approximation error tiny.
+        let n = (dt.abs() / PI_2_32).ceil().max(1.) as usize;
+        let step = dt / n as f32;
+        let k = 4. / 3. * (step / 4.).tan();
+        for i in 0..n {
+            let (ta, tb) = (t1 + step * i as f32, t1 + step * (i + 1) as f32);
+            let (pa, da, pb, db) = (point(ta), deriv(ta), point(tb), deriv(tb));
+            self.cubic_to(
+                pa.0 + k * da.0,
+                pa.1 + k * da.1,
+                pb.0 - k * db.0,
+                pb.1 - k * db.1,
+                pb.0,
+                pb.1,
+            );
+        }
+    }
+
+    /// Approximates the path with polylines, returning each subpath's points and whether it was closed.
+    pub(crate) fn flatten(&self) -> Vec<(Vec<(f32, f32)>, bool)> {
+        let mut out: Vec<(Vec<(f32, f32)>, bool)> = Vec::new();
+        let mut cur = (0., 0.);
+        for seg in &self.segments {
+            match *seg {
+                PathSegment::MoveTo(x, y) => {
+                    out.push((vec![(x, y)], false));
+                    cur = (x, y);
+                    continue;
+                }
+                PathSegment::Close => {
+                    if let Some(last) = out.last_mut() {
+                        last.1 = true;
+                        cur = last.0[0];
+                        // Segments after a close start a new subpath from the same point.
+                        out.push((vec![cur], false));
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            let Some((points, _)) = out.last_mut() else {
+                continue;
+            };
+            match *seg {
+                PathSegment::LineTo(x, y) => {
+                    points.push((x, y));
+                    cur = (x, y);
+                }
+                PathSegment::QuadTo(x1, y1, x, y) => {
+                    let dd = ((cur.0 - 2. * x1 + x).powi(2) + (cur.1 - 2. * y1 + y).powi(2)).sqrt();
+                    let n = ((dd / (4. * TOLERANCE)).sqrt().ceil() as usize).clamp(1, 1000);
+                    for i in 1..=n {
+                        let t = i as f32 / n as f32;
+                        let mt = 1. - t;
+                        points.push((
+                            mt * mt * cur.0 + 2. * mt * t * x1 + t * t * x,
+                            mt * mt * cur.1 + 2. * mt * t * y1 + t * t * y,
+                        ));
+                    }
+                    cur = (x, y);
+                }
+                PathSegment::CubicTo(x1, y1, x2, y2, x, y) => {
+                    let dd1 = (cur.0 - 2. * x1 + x2).powi(2) + (cur.1 - 2. * y1 + y2).powi(2);
+                    let dd2 = (x1 - 2. * x2 + x).powi(2) + (y1 - 2. * y2 + y).powi(2);
+                    let dd = dd1.max(dd2).sqrt();
+                    let n = ((3. * dd / (4. * TOLERANCE)).sqrt().ceil() as usize).clamp(1, 1000);
+                    for i in 1..=n {
+                        let t = i as f32 / n as f32;
+                        let mt = 1. - t;
+                        let (a, b, c, d) =
+                            (mt * mt * mt, 3. * mt * mt * t, 3. * mt * t * t, t * t * t);
+                        points.push((
+                            a * cur.0 + b * x1 + c * x2 + d * x,
+                            a * cur.1 + b * y1 + c * y2 + d * y,
+                        ));
+                    }
+                    cur = (x, y);
+                }
+                PathSegment::MoveTo(..) | PathSegment::Close => unreachable!(),
+            }
+        }
+        out.retain(|(points, _)| points.len() > 1);
+        out
+    }
+
+    /// Adds a rectangle as a closed subpath.
+    pub fn rect(&mut self, x: f32, y: f32, w: f32, h: f32) -> &mut Self {
+        self.move_to(x, y)
+            .line_to(x + w, y)
+            .line_to(x + w, y + h)
+            .line_to(x, y + h)
+            .close()
+    }
+    /// Adds a rounded rectangle as a closed subpath. Radii are clamped like [`CornerRadii::clamp`].
+    pub fn round_rect(&mut self, x: f32, y: f32, w: f32, h: f32, radii: CornerRadii) -> &mut Self {
+        let f = radii.fit_factor(w, h);
+        let scale = |r: Radius| (r.x as f32 * f, r.y as f32 * f);
+        let (tl, tr, br, bl) = (
+            scale(radii.tl),
+            scale(radii.tr),
+            scale(radii.br),
+            scale(radii.bl),
+        );
+        self.move_to(x + tl.0, y)
+            .line_to(x + w - tr.0, y)
+            .arc(x + w - tr.0, y + tr.1, tr.0, tr.1, PI_2_32, -PI_2_32)
+            .line_to(x + w, y + h - br.1)
+            .arc(x + w - br.0, y + h - br.1, br.0, br.1, 0., -PI_2_32)
+            .line_to(x + bl.0, y + h)
+            .arc(x + bl.0, y + h - bl.1, bl.0, bl.1, 3. * PI_2_32, -PI_2_32)
+            .line_to(x, y + tl.1)
+            .arc(x + tl.0, y + tl.1, tl.0, tl.1, 2. * PI_2_32, -PI_2_32)
+            .close()
+    }
+
+    /// Outline of a stroke along the path, as polygons to fill with [`draw::FillRule::NonZero`].
+    pub(crate) fn stroke_outline(&self, style: &StrokeStyle) -> Vec<Vec<(f32, f32)>> {
+        let mut out = Vec::new();
+        let period = style.dash.iter().sum::<f32>();
+        let dashed = !style.dash.is_empty()
+            && style.dash.iter().all(|d| d.is_finite() && *d >= 0.)
+            && period >= MIN_DASH_PERIOD;
+        for (points, closed) in self.flatten() {
+            let mut length: f32 = points
+                .windows(2)
+                .map(|s| ((s[1].0 - s[0].0).powi(2) + (s[1].1 - s[0].1).powi(2)).sqrt())
+                .sum();
+            if let (true, Some(first), Some(last)) = (closed, points.first(), points.last()) {
+                length += ((first.0 - last.0).powi(2) + (first.1 - last.1).powi(2)).sqrt();
+            }
+            if dashed && length / period * style.dash.len() as f32 <= MAX_DASHES {
+                for dash in dash_polyline(&points, closed, &style.dash, style.dash_offset) {
+                    stroke_polyline(&mut out, dash, false, style);
+                }
+            } else {
+                stroke_polyline(&mut out, points, closed, style);
+            }
+        }
+        out
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineCap {
+    /// The stroke ends exactly at the end point.
+    #[default]
+    Butt,
+    /// The stroke ends with a half circle around the end point.
+    Round,
+    /// The stroke extends past the end point by half its width.
+    Square,
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineJoin {
+    /// Outer edges are extended until they meet, falling back to [`LineJoin::Bevel`] past the miter limit.
+    #[default]
+    Miter,
+    /// Corners are rounded with a circle around the vertex.
+    Round,
+    /// Outer corners are cut off with a straight line.
+    Bevel,
+}
+
+/// How outlines are stroked.
+#[derive(Clone)]
+pub struct StrokeStyle {
+    /// Line width in pixels.
+    pub width: f32,
+    pub cap: LineCap,
+    pub join: LineJoin,
+    /// Maximum ratio of miter length to line width before a miter join becomes a bevel.
+    pub miter_limit: f32,
+    /// Alternating dash and gap lengths in pixels, repeated along each subpath. Empty for a solid line.
+    ///
+    /// An odd number of lengths is repeated twice, like in SVG. Each dash gets its own caps.
+    pub dash: Vec<f32>,
+    /// Distance into the dash pattern at which each subpath starts. Animating it gives marching ants.
+    pub dash_offset: f32,
+}
+
+impl StrokeStyle {
+    pub fn new(width: f32, cap: LineCap, join: LineJoin) -> Self {
+        Self {
+            width,
+            cap,
+            join,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self {
+            width: 1.,
+            cap: LineCap::Butt,
+            join: LineJoin::Miter,
+            miter_limit: 4.,
+            dash: Vec::new(),
+            dash_offset: 0.,
+        }
+    }
+}
+
+/// Splits a polyline into dashes. The pattern must have a positive length.
+fn dash_polyline(
+    points: &[(f32, f32)],
+    closed: bool,
+    dash: &[f32],
+    offset: f32,
+) -> Vec<Vec<(f32, f32)>> {
+    let pattern = if dash.len() % 2 == 1 {
+        dash.repeat(2)
+    } else {
+        dash.to_vec()
+    };
+    let mut points = points.to_vec();
+    if closed {
+        points.push(points[0]);
+    }
+
+    let mut phase = offset.rem_euclid(pattern.iter().sum());
+    let mut i = 0;
+    while phase >= pattern[i] {
+        phase -= pattern[i];
+        i = (i + 1) % pattern.len();
+    }
+    let mut remaining = pattern[i] - phase;
+    let starts_on = i % 2 == 0;
+    let mut on = starts_on;
+
+    let mut dashes = Vec::new();
+    let mut current = if on { vec![points[0]] } else { Vec::new() };
+    for seg in points.windows(2) {
+        let (a, b) = (seg[0], seg[1]);
+        let len = ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2)).sqrt();
+        let mut pos = 0.;
+        while len - pos > remaining {
+            pos += remaining;
+            let t = pos / len;
+            let p = (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t);
+            // Ends the current dash, or starts the next one.
+            current.push(p);
+            if on {
+                dashes.push(mem::take(&mut current));
+            }
+            on = !on;
+            i = (i + 1) % pattern.len();
+            remaining = pattern[i];
+        }
+        remaining -= len - pos;
+        if on {
+            current.push(b);
+        }
+    }
+    if on && !current.is_empty() {
+        // A dash running over the start of a closed subpath continues into the first one.
+        if closed && starts_on && !dashes.is_empty() {
+            current.extend_from_slice(&dashes[0][1..]);
+            dashes[0] = current;
+        } else {
+            dashes.push(current);
+        }
+    }
+    dashes
+}
+
+/// Adds the stroke outline of a polyline.
+///
+/// Each segment becomes a quad, with extra polygons for caps and joins, all wound the same way so their union is filled.
+fn stroke_polyline(
+    out: &mut Vec<Vec<(f32, f32)>>,
+    mut points: Vec<(f32, f32)>,
+    closed: bool,
+    style: &StrokeStyle,
+) {
+    let hw = style.width / 2.;
+    if hw <= 0. || !hw.is_finite() {
+        return;
+    }
+    points.dedup_by(|a, b| a.0 == b.0 && a.1 == b.1);
+    if closed && points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    if points.len() == 1 && !closed {
+        // Zero length subpaths still get their caps.
+        let p = points[0];
+        match style.cap {
+            LineCap::Butt => {}
+            LineCap::Round => push_piece(out, circle_polygon(p, hw)),
+            LineCap::Square => push_piece(
+                out,
+                vec![
+                    (p.0 - hw, p.1 - hw),
+                    (p.0 + hw, p.1 - hw),
+                    (p.0 + hw, p.1 + hw),
+                    (p.0 - hw, p.1 + hw),
+                ],
+            ),
+        }
+    }
+    if points.len() < 2 {
+        return;
+    }
+    let n = points.len();
+    let seg_count = if closed { n } else { n - 1 };
+    let dir = |i: usize| {
+        let (a, b) = (points[i % n], points[(i + 1) % n]);
+        let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+        let len = (dx * dx + dy * dy).sqrt();
+        (dx / len, dy / len)
+    };
+    let offs = |p: (f32, f32), v: (f32, f32), k: f32| (p.0 + v.0 * k, p.1 + v.1 * k);
+    for i in 0..seg_count {
+        let (a, b) = (points[i], points[(i + 1) % n]);
+        let d = dir(i);
+        let nm = (-d.1, d.0);
+        push_piece(
+            out,
+            vec![
+                offs(a, nm, hw),
+                offs(b, nm, hw),
+                offs(b, nm, -hw),
+                offs(a, nm, -hw),
+            ],
+        );
+    }
+
+    if !closed {
+        // Square caps extend backwards from the start and forwards from the end.
+        for (p, d, ext) in [(points[0], dir(0), -hw), (points[n - 1], dir(n - 2), hw)] {
+            let nm = (-d.1, d.0);
+            match style.cap {
+                LineCap::Butt => {}
+                LineCap::Round => push_piece(out, circle_polygon(p, hw)),
+                LineCap::Square => {
+                    let q = offs(p, d, ext);
+                    push_piece(
+                        out,
+                        vec![
+                            offs(p, nm, hw),
+                            offs(q, nm, hw),
+                            offs(q, nm, -hw),
+                            offs(p, nm, -hw),
+                        ],
+                    );
+                }
+            }
+        }
+    }
+
+    let joins = if closed { 0..n } else { 1..n - 1 };
+    for i in joins {
+        let p = points[i];
+        let (d1, d2) = (dir(i + n - 1), dir(i));
+        let (n1, n2) = ((-d1.1, d1.0), (-d2.1, d2.0));
+        // The outer side of the corner is the one the next segment turns away from.
+        let side = if n1.0 * d2.0 + n1.1 * d2.1 > 0. {
+            -hw
+        } else {
+            hw
+        };
+        let (o1, o2) = (offs(p, n1, side), offs(p, n2, side));
+        let cos = n1.0 * n2.0 + n1.1 * n2.1;
+        match style.join {
+            LineJoin::Round => push_piece(out, circle_polygon(p, hw)),
+            LineJoin::Miter if (2. / (1. + cos)).sqrt() <= style.miter_limit => {
+                let k = side / (1. + cos);
+                let m = (p.0 + (n1.0 + n2.0) * k, p.1 + (n1.1 + n2.1) * k);
+                push_piece(out, vec![p, o1, m, o2]);
+            }
+            LineJoin::Miter | LineJoin::Bevel => push_piece(out, vec![p, o1, o2]),
+        }
+    }
+}
+
+/// Polygon approximating a circle within [`TOLERANCE`].
+fn circle_polygon(center: (f32, f32), radius: f32) -> Vec<(f32, f32)> {
+    let step = 2. * (1. - TOLERANCE / radius).clamp(-1., 1.).acos();
+    let n = (TAU32 / step).ceil().clamp(8., 256.) as usize;
+    (0..n)
+        .map(|i| {
+            let (sin, cos) = (i as f32 * TAU32 / n as f32).sin_cos();
+            (center.0 + radius * cos, center.1 + radius * sin)
+        })
+        .collect()
+}
+
+/// Adds a polygon to a stroke outline, making it wind positively.
+fn push_piece(out: &mut Vec<Vec<(f32, f32)>>, mut poly: Vec<(f32, f32)>) {
+    let mut area = 0.;
+    for (i, a) in poly.iter().enumerate() {
+        let b = poly[(i + 1) % poly.len()];
+        area += a.0 * b.1 - b.0 * a.1;
+    }
+    if area.abs() < 1e-6 {
+        return;
+    }
+    if area < 0. {
+        poly.reverse();
+    }
+    out.push(poly);
+}
+
+/// Horizontal and vertical radius of an ellipse or an elliptical corner.
+#[derive(Clone, Copy, Default)]
+pub struct Radius {
+    pub x: u32,
+    pub y: u32,
+}
+
+impl Radius {
+    pub fn new(x: u32, y: u32) -> Self {
+        Self { x, y }
+    }
+}
+
+impl From<u32> for Radius {
+    /// A circular radius.
+    #[inline]
+    fn from(r: u32) -> Self {
+        Radius { x: r, y: r }
+    }
+}
+
+impl From<(u32, u32)> for Radius {
+    #[inline]
+    fn from((x, y): (u32, u32)) -> Self {
+        Radius { x, y }
+    }
+}
+
+/// Radii of each corner of a rounded rectangle: top left, top right, bottom right and bottom left.
+#[derive(Clone, Copy, Default)]
+pub struct CornerRadii {
+    pub tl: Radius,
+    pub tr: Radius,
+    pub br: Radius,
+    pub bl: Radius,
+}
+
+impl CornerRadii {
+    pub fn new(tl: Radius, tr: Radius, br: Radius, bl: Radius) -> Self {
+        Self { tl, tr, br, bl }
+    }
+    /// Scales all radii down by the same factor if adjacent ones add up to more than the matching side of `size`, like CSS does.
+    pub fn clamp(&self, size: Size) -> Self {
+        let f = self.fit_factor(size.w as f32, size.h as f32);
+        if f >= 1. {
+            return *self;
+        }
+        let scale = |r: Radius| Radius {
+            x: (r.x as f32 * f) as u32,
+            y: (r.y as f32 * f) as u32,
+        };
+        Self {
+            tl: scale(self.tl),
+            tr: scale(self.tr),
+            br: scale(self.br),
+            bl: scale(self.bl),
+        }
+    }
+    /// Factor (at most 1) making the radii fit a `w` by `h` rectangle.
+    pub(crate) fn fit_factor(&self, w: f32, h: f32) -> f32 {
+        let ratio = |side: f32, a: u32, b: u32| {
+            let sum = a as f32 + b as f32;
+            if sum == 0. {
+                1.
+            } else {
+                side.max(0.) / sum
+            }
+        };
+        ratio(w, self.tl.x, self.tr.x)
+            .min(ratio(w, self.bl.x, self.br.x))
+            .min(ratio(h, self.tl.y, self.bl.y))
+            .min(ratio(h, self.tr.y, self.br.y))
+            .min(1.)
+    }
+}
+
+impl From<Radius> for CornerRadii {
+    #[inline]
+    fn from(r: Radius) -> Self {
+        CornerRadii {
+            tl: r,
+            tr: r,
+            br: r,
+            bl: r,
+        }
+    }
+}
+
+impl From<u32> for CornerRadii {
+    #[inline]
+    fn from(r: u32) -> Self {
+        Radius::from(r).into()
+    }
+}
+
+impl From<[u32; 4]> for CornerRadii {
+    /// Circular radii, clockwise from top left.
+    #[inline]
+    fn from([tl, tr, br, bl]: [u32; 4]) -> Self {
+        CornerRadii {
+            tl: tl.into(),
+            tr: tr.into(),
+            br: br.into(),
+            bl: bl.into(),
+        }
+    }
+}
+
 impl From<Size> for Rect {
     #[inline]
     fn from(size: Size) -> Self {
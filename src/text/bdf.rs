@@ -0,0 +1,104 @@
+use std::{collections::HashMap, rc::Rc};
+
+use super::{bitmap::unpack_rows, BitmapFont, FontError, Glyph};
+use crate::{Offset, Size};
+
+impl BitmapFont {
+    /// Parses an X11 BDF font. Encodings are taken as Unicode code points, which holds for ISO 10646 and ISO 8859-1 fonts.
+    pub fn from_bdf(data: &[u8]) -> Result<Self, FontError> {
+        let text = std::str::from_utf8(data).map_err(|_| FontError::Malformed("not UTF-8"))?;
+        let mut lines = text.lines().map(str::trim);
+        if !lines.next().is_some_and(|l| l.starts_with("STARTFONT")) {
+            return Err(FontError::Malformed("missing STARTFONT"));
+        }
+
+        let mut bbox = [0i32; 4];
+        let mut ascent = None;
+        let mut descent = None;
+        let mut default_char = None;
+        let mut default_width = None;
+        let mut glyphs = HashMap::new();
+
+        while let Some(line) = lines.next() {
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("FONTBOUNDINGBOX") => bbox = numbers(words)?,
+                Some("FONT_ASCENT") => ascent = Some(number(words.next())?),
+                Some("FONT_DESCENT") => descent = Some(number(words.next())?),
+                Some("DEFAULT_CHAR") => default_char = Some(number(words.next())?),
+                Some("DWIDTH") => default_width = Some(number(words.next())?),
+                Some("STARTCHAR") => {
+                    let mut encoding = -1;
+                    let mut advance = default_width.unwrap_or(bbox[0]);
+                    let mut char_bbox = bbox;
+                    let mut bitmap = Vec::new();
+                    let mut in_bitmap = false;
+                    for line in lines.by_ref() {
+                        let mut words = line.split_whitespace();
+                        match words.next() {
+                            Some("ENCODING") => encoding = number(words.next())?,
+                            Some("DWIDTH") => advance = number(words.next())?,
+                            Some("BBX") => char_bbox = numbers(words)?,
+                            Some("BITMAP") => in_bitmap = true,
+                            Some("ENDCHAR") => break,
+                            Some(row)
+                                if in_bitmap && row.bytes().all(|b| b.is_ascii_hexdigit()) =>
+                            {
+                                for i in (0..row.len() - row.len() % 2).step_by(2) {
+                                    bitmap.push(u8::from_str_radix(&row[i..i + 2], 16).unwrap());
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                    let Some(c) = u32::try_from(encoding).ok().and_then(char::from_u32) else {
+                        continue;
+                    };
+                    let [w, h, x, y] = char_bbox;
+                    let size = Size::new(w.max(0) as u32, h.max(0) as u32);
+                    let coverage = unpack_rows(&bitmap, size)?;
+                    let top = y
+                        .checked_add(h)
+                        .and_then(i32::checked_neg)
+                        .ok_or(FontError::Malformed("invalid glyph bounding box"))?;
+                    glyphs.insert(
+                        c,
+                        Rc::new(Glyph {
+                            size,
+                            offset: Offset::new(x, top),
+                            advance,
+                            coverage,
+                        }),
+                    );
+                }
+                Some("ENDFONT") => break,
+                _ => {}
+            }
+        }
+
+        let invalid = || FontError::Malformed("invalid font bounding box");
+        let ascent = ascent
+            .or(bbox[1].checked_add(bbox[3]))
+            .ok_or_else(invalid)?;
+        let descent = descent.or(bbox[3].checked_neg()).ok_or_else(invalid)?;
+        let mut font = BitmapFont::new(glyphs, ascent, descent);
+        if let Some(c) = default_char.and_then(|c| char::from_u32(c as u32)) {
+            font.set_replacement(c);
+        }
+        Ok(font)
+    }
+}
+
+fn number(word: Option<&str>) -> Result<i32, FontError> {
+    word.and_then(|w| w.parse().ok())
+        .ok_or(FontError::Malformed("invalid number"))
+}
+
+fn numbers<'a>(mut words: impl Iterator<Item = &'a str>) -> Result<[i32; 4], FontError> {
+    Ok([
+        number(words.next())?,
+        number(words.next())?,
+        number(words.next())?,
+        number(words.next())?,
+    ])
+}
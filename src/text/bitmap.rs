@@ -0,0 +1,100 @@
+use std::{collections::HashMap, rc::Rc};
+
+use super::{Font, FontError, Glyph};
+use crate::{Offset, Size};
+
+/// A font made of prerendered glyphs, usually loaded from a BDF or PSF file.
+#[derive(Clone)]
+pub struct BitmapFont {
+    pub(crate) glyphs: HashMap<char, Rc<Glyph>>,
+    pub(crate) ascent: i32,
+    pub(crate) descent: i32,
+    pub(crate) replacement: Rc<Glyph>,
+}
+
+impl BitmapFont {
+    /// Creates a font from glyphs keyed by character. The replacement glyph is U+FFFD or `?` if the font has one, else an empty box.
+    pub fn new(glyphs: HashMap<char, Rc<Glyph>>, ascent: i32, descent: i32) -> Self {
+        let replacement = ['\u{FFFD}', '?']
+            .iter()
+            .find_map(|c| glyphs.get(c).cloned())
+            .unwrap_or_else(|| Rc::new(empty_box(ascent, descent)));
+        Self {
+            glyphs,
+            ascent,
+            descent,
+            replacement,
+        }
+    }
+    /// Use the glyph of `c` for missing characters, if the font has it.
+    pub fn set_replacement(&mut self, c: char) {
+        if let Some(glyph) = self.glyphs.get(&c) {
+            self.replacement = glyph.clone();
+        }
+    }
+}
+
+/// A box outline as tall as the font, and half as wide.
+fn empty_box(ascent: i32, descent: i32) -> Glyph {
+    let h = (ascent + descent).max(2) as u32;
+    let w = (h / 2).max(2);
+    let coverage = (0..w * h)
+        .map(|i| {
+            let (x, y) = (i % w, i / w);
+            if x == 0 || y == 0 || x == w - 1 || y == h - 1 {
+                255
+            } else {
+                0
+            }
+        })
+        .collect();
+    Glyph {
+        size: Size::new(w, h),
+        offset: Offset::new(0, -ascent),
+        advance: w as i32 + 1,
+        coverage,
+    }
+}
+
+/// Largest glyph width or height accepted from font files.
+const MAX_GLYPH_SIZE: u32 = 1024;
+
+/// Expands rows of packed bits, most significant bit first, each row starting on a byte boundary.
+///
+/// Fails if the glyph is implausibly large or `data` is too short for its rows.
+pub(crate) fn unpack_rows(data: &[u8], size: Size) -> Result<Vec<u8>, FontError> {
+    if size.w > MAX_GLYPH_SIZE || size.h > MAX_GLYPH_SIZE {
+        return Err(FontError::Malformed("glyph too large"));
+    }
+    let stride = size.w.div_ceil(8) as usize;
+    if stride * size.h as usize > data.len() {
+        return Err(FontError::Malformed("truncated glyph bitmap"));
+    }
+    let mut coverage = Vec::with_capacity(size.w as usize * size.h as usize);
+    for y in 0..size.h as usize {
+        for x in 0..size.w as usize {
+            let byte = data[y * stride + x / 8];
+            coverage.push(if byte & (0x80 >> (x % 8)) != 0 {
+                255
+            } else {
+                0
+            });
+        }
+    }
+    Ok(coverage)
+}
+
+impl Font for BitmapFont {
+    fn ascent(&self) -> i32 {
+        self.ascent
+    }
+    fn descent(&self) -> i32 {
+        self.descent
+    }
+    fn has_glyph(&self, c: char) -> bool {
+        self.glyphs.contains_key(&c)
+    }
+    fn glyph(&self, c: char) -> Rc<Glyph> {
+        self.glyphs.get(&c).unwrap_or(&self.replacement).clone()
+    }
+}
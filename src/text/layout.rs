@@ -0,0 +1,155 @@
+use super::{BuiltinFont, Font};
+use crate::color::Color;
+
+/// Horizontal alignment of lines.
+#[derive(Clone, Copy, Default)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+    /// Stretches the spaces between words so lines fill the width. The last line of a paragraph is left-aligned.
+    Justify,
+}
+
+/// Vertical alignment of the block of lines.
+#[derive(Clone, Copy, Default)]
+pub enum VerticalAlign {
+    #[default]
+    Top,
+    Middle,
+    Bottom,
+}
+
+/// How to lay out text in a [`Rect`](crate::Rect), for [`Drawable::text_in_rect`](crate::Drawable::text_in_rect).
+#[derive(Clone)]
+pub struct TextStyle<'a> {
+    pub font: &'a dyn Font,
+    pub color: Color,
+    pub align: TextAlign,
+    pub vertical_align: VerticalAlign,
+    /// Multiplier of the font's line height.
+    pub line_height: f32,
+    /// Wraps lines at spaces, or inside words longer than the width.
+    pub wrap: bool,
+    /// Truncates overflowing text with "…".
+    pub ellipsis: bool,
+}
+
+impl<'a> TextStyle<'a> {
+    pub fn new(font: &'a dyn Font, color: Color) -> Self {
+        Self {
+            font,
+            color,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for TextStyle<'_> {
+    /// Black, wrapped text in the [`BuiltinFont`], at the top left.
+    fn default() -> Self {
+        Self {
+            font: &BuiltinFont,
+            color: Color::BLACK,
+            align: TextAlign::Left,
+            vertical_align: VerticalAlign::Top,
+            line_height: 1.,
+            wrap: true,
+            ellipsis: false,
+        }
+    }
+}
+
+/// A laid out line of words, separated by single spaces.
+#[derive(Default)]
+pub(crate) struct Line {
+    pub words: Vec<String>,
+    pub width: i32,
+    /// Whether the line ends a paragraph, or was truncated.
+    pub last: bool,
+}
+
+impl Line {
+    fn measure(&mut self, font: &dyn Font) {
+        let spaces = self.words.len().saturating_sub(1) as i32;
+        self.width = self.words.iter().map(|w| width(font, w)).sum::<i32>() + spaces * space(font);
+    }
+}
+
+pub(crate) fn width(font: &dyn Font, text: &str) -> i32 {
+    font.measure_text(text).w as i32
+}
+
+pub(crate) fn space(font: &dyn Font) -> i32 {
+    font.glyph(' ').advance
+}
+
+/// Breaks text into lines no wider than `max_width`, at `\n` and, if `wrap` is set, at spaces.
+pub(crate) fn break_lines(text: &str, font: &dyn Font, max_width: i32, wrap: bool) -> Vec<Line> {
+    let space = space(font);
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let mut line = Line::default();
+        for word in paragraph.split(' ').filter(|w| !w.is_empty()) {
+            let mut word = word.to_string();
+            let mut w = width(font, &word);
+            if wrap && !line.words.is_empty() && line.width + space + w > max_width {
+                lines.push(std::mem::take(&mut line));
+            }
+            // Split words that can't fit on a line of their own.
+            while wrap && line.words.is_empty() && w > max_width {
+                let mut split = 0;
+                for (i, _) in word.char_indices().skip(1) {
+                    if width(font, &word[..i]) > max_width {
+                        break;
+                    }
+                    split = i;
+                }
+                if split == 0 {
+                    split = word.chars().next().map_or(word.len(), char::len_utf8);
+                }
+                if split == word.len() {
+                    break;
+                }
+                let rest = word.split_off(split);
+                lines.push(Line {
+                    width: width(font, &word),
+                    words: vec![word],
+                    last: false,
+                });
+                word = rest;
+                w = width(font, &word);
+            }
+            if !line.words.is_empty() {
+                line.width += space;
+            }
+            line.width += w;
+            line.words.push(word);
+        }
+        line.last = true;
+        lines.push(line);
+    }
+    lines
+}
+
+/// Shortens a line so that it ends with an ellipsis within `max_width`.
+pub(crate) fn ellipsize(line: &mut Line, font: &dyn Font, max_width: i32) {
+    let ellipsis = if font.has_glyph('…') { "…" } else { "..." };
+    let ellipsis_width = width(font, ellipsis);
+    while line.width + ellipsis_width > max_width {
+        let Some(word) = line.words.last_mut() else {
+            break;
+        };
+        if word.pop().is_none() || word.is_empty() {
+            line.words.pop();
+        }
+        line.measure(font);
+    }
+    match line.words.last_mut() {
+        Some(word) => word.push_str(ellipsis),
+        None => line.words.push(ellipsis.to_string()),
+    }
+    line.last = true;
+    line.measure(font);
+}
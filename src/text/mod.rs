@@ -0,0 +1,105 @@
+use std::{error::Error, fmt, rc::Rc};
+
+use crate::{Offset, Size};
+
+mod bdf;
+mod bitmap;
+mod builtin;
+mod layout;
+mod psf;
+#[cfg(feature = "truetype")]
+mod truetype;
+
+pub use bitmap::BitmapFont;
+pub use builtin::BuiltinFont;
+pub(crate) use layout::{break_lines, ellipsize, space, width};
+pub use layout::{TextAlign, TextStyle, VerticalAlign};
+#[cfg(feature = "truetype")]
+pub use truetype::{ScaledFont, TrueTypeFont};
+
+#[derive(Clone, Debug)]
+pub enum FontError {
+    /// The data isn't a valid font file, with a short description of the problem.
+    Malformed(&'static str),
+    /// The font uses a feature that isn't supported.
+    Unsupported(&'static str),
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed(reason) => write!(f, "malformed font: {reason}"),
+            Self::Unsupported(feature) => write!(f, "unsupported font feature: {feature}"),
+        }
+    }
+}
+
+impl Error for FontError {}
+
+/// A rasterized glyph.
+pub struct Glyph {
+    pub size: Size,
+    /// Top left corner of the bitmap, relative to the pen position on the baseline.
+    pub offset: Offset,
+    /// Horizontal distance to the next pen position.
+    pub advance: i32,
+    /// Row-major coverage values, 255 being fully covered.
+    pub coverage: Vec<u8>,
+}
+
+/// A source of glyphs for drawing text.
+pub trait Font {
+    /// Distance from the top of a line to the baseline.
+    fn ascent(&self) -> i32;
+
+    /// Distance from the baseline to the bottom of a line.
+    fn descent(&self) -> i32;
+
+    /// Extra space between lines.
+    fn line_gap(&self) -> i32 {
+        0
+    }
+
+    /// Distance between the baselines of consecutive lines.
+    fn line_height(&self) -> i32 {
+        self.ascent() + self.descent() + self.line_gap()
+    }
+
+    fn has_glyph(&self, c: char) -> bool;
+
+    /// Get the glyph of a character, or a replacement glyph if the font lacks it.
+    fn glyph(&self, c: char) -> Rc<Glyph>;
+
+    /// Adjustment of the advance between two characters.
+    fn kerning(&self, _left: char, _right: char) -> i32 {
+        0
+    }
+
+    /// Get the size of the text's bounding box when drawn. Lines are separated by `\n`.
+    fn measure_text(&self, text: &str) -> Size {
+        let mut lines = 0;
+        let mut width = 0;
+        for line in text.split('\n') {
+            lines += 1;
+            let mut w = 0;
+            let mut prev = None;
+            for c in line.chars() {
+                if let Some(p) = prev {
+                    w += self.kerning(p, c);
+                }
+                w += self.glyph(c).advance;
+                prev = Some(c);
+            }
+            width = width.max(w);
+        }
+        Size::new(
+            width.max(0) as u32,
+            (self.ascent() + self.descent() + (lines - 1) * self.line_height()).max(0) as u32,
+        )
+    }
+}
+
+/// Get the size of the text drawn with [`BuiltinFont`].
+pub fn measure_text(text: &str) -> Size {
+    BuiltinFont.measure_text(text)
+}
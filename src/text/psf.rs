@@ -0,0 +1,104 @@
+use std::{collections::HashMap, rc::Rc};
+
+use super::{bitmap::unpack_rows, BitmapFont, FontError, Glyph};
+use crate::{Offset, Size};
+
+const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+const PSF2_MAGIC: [u8; 4] = [0x72, 0xb5, 0x4a, 0x86];
+
+impl BitmapFont {
+    /// Parses a Linux console PSF1 or PSF2 font.
+    ///
+    /// PSF fonts have no baseline, so it's placed a quarter of the glyph height above the bottom.
+    /// Without a Unicode table, glyph indices are used as code points.
+    pub fn from_psf(data: &[u8]) -> Result<Self, FontError> {
+        let truncated = FontError::Malformed("truncated");
+        let (count, size, bytes_per_glyph, glyphs_start, table) = if data.starts_with(&PSF1_MAGIC) {
+            let mode = *data.get(2).ok_or(truncated.clone())?;
+            let height = *data.get(3).ok_or(truncated.clone())? as u32;
+            let count = if mode & 1 != 0 { 512 } else { 256 };
+            let has_table = mode & 0x6 != 0;
+            (count, Size::new(8, height), height as usize, 4, has_table)
+        } else if data.starts_with(&PSF2_MAGIC) {
+            let field = |i: usize| {
+                data.get(i * 4..i * 4 + 4)
+                    .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+                    .ok_or(truncated.clone())
+            };
+            let header_size = field(2)? as usize;
+            let flags = field(3)?;
+            let count = field(4)? as usize;
+            let bytes_per_glyph = field(5)? as usize;
+            let size = Size::new(field(7)?, field(6)?);
+            if bytes_per_glyph == 0 {
+                return Err(FontError::Malformed("empty glyphs"));
+            }
+            (count, size, bytes_per_glyph, header_size, flags & 1 != 0)
+        } else {
+            return Err(FontError::Malformed("not a PSF font"));
+        };
+
+        let glyphs_end = count
+            .checked_mul(bytes_per_glyph)
+            .and_then(|len| glyphs_start.checked_add(len))
+            .ok_or(truncated.clone())?;
+        let bitmaps = data.get(glyphs_start..glyphs_end).ok_or(truncated)?;
+        let height = size.h as i32;
+        let descent = height / 4;
+        let glyph = |i: usize| {
+            Ok::<_, FontError>(Rc::new(Glyph {
+                size,
+                offset: Offset::new(0, descent - height),
+                advance: size.w as i32,
+                coverage: unpack_rows(
+                    &bitmaps[i * bytes_per_glyph..(i + 1) * bytes_per_glyph],
+                    size,
+                )?,
+            }))
+        };
+
+        let mut glyphs = HashMap::new();
+        if !table {
+            for i in 0..count {
+                if let Some(c) = char::from_u32(i as u32) {
+                    glyphs.insert(c, glyph(i)?);
+                }
+            }
+        } else if data.starts_with(&PSF1_MAGIC) {
+            // UCS-2 entries ending with 0xFFFF, with combining sequences after 0xFFFE.
+            let mut entries = data[glyphs_end..]
+                .chunks_exact(2)
+                .map(|b| u16::from_le_bytes([b[0], b[1]]));
+            for i in 0..count {
+                let g = glyph(i)?;
+                let mut sequence = false;
+                for entry in entries.by_ref() {
+                    match entry {
+                        0xFFFF => break,
+                        0xFFFE => sequence = true,
+                        _ if sequence => {}
+                        _ => {
+                            if let Some(c) = char::from_u32(entry as u32) {
+                                glyphs.entry(c).or_insert_with(|| g.clone());
+                            }
+                        }
+                    }
+                }
+            }
+        } else {
+            // UTF-8 entries ending with 0xFF, with combining sequences after 0xFE.
+            let mut entries = data[glyphs_end..].split(|&b| b == 0xFF);
+            for i in 0..count {
+                let Some(entry) = entries.next() else {
+                    break;
+                };
+                let g = glyph(i)?;
+                let singles = entry.split(|&b| b == 0xFE).next().unwrap_or_default();
+                for c in std::str::from_utf8(singles).unwrap_or_default().chars() {
+                    glyphs.entry(c).or_insert_with(|| g.clone());
+                }
+            }
+        }
+        Ok(BitmapFont::new(glyphs, height - descent, descent))
+    }
+}
@@ -0,0 +1,509 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use super::{Font, FontError, Glyph};
+use crate::{
+    draw::{rasterize, FillRule},
+    Offset, Path, Size,
+};
+
+/// Outlines wider or taller than this many ems are treated as broken, and drawn as the missing glyph.
+const MAX_GLYPH_EMS: f32 = 8.;
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+}
+
+fn read_i16(data: &[u8], offset: usize) -> Option<i16> {
+    read_u16(data, offset).map(|v| v as i16)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+/// A TrueType font with `glyf` outlines. Use [`Self::scaled`] to draw text with it.
+///
+/// Rasterized glyphs are cached per size.
+pub struct TrueTypeFont {
+    data: Vec<u8>,
+    units_per_em: u16,
+    long_loca: bool,
+    num_glyphs: u16,
+    ascender: i16,
+    descender: i16,
+    line_gap: i16,
+    num_h_metrics: u16,
+    cmap: usize,
+    loca: usize,
+    glyf: usize,
+    hmtx: usize,
+    /// Offset and count of format 0 kerning pairs.
+    kern: Option<(usize, usize)>,
+    cache: RefCell<HashMap<(u32, u16), Rc<Glyph>>>,
+}
+
+impl TrueTypeFont {
+    /// Parses a TrueType or OpenType font file. Collections use their first font, and CFF outlines aren't supported.
+    pub fn parse(data: Vec<u8>) -> Result<Self, FontError> {
+        let malformed = FontError::Malformed("truncated table");
+        let mut start = 0;
+        match data.get(0..4) {
+            Some(b"ttcf") => start = read_u32(&data, 12).ok_or(malformed.clone())? as usize,
+            Some(b"OTTO") => return Err(FontError::Unsupported("CFF outlines")),
+            Some([0, 1, 0, 0] | b"true") => {}
+            _ => return Err(FontError::Malformed("not a TrueType font")),
+        }
+
+        let num_tables = read_u16(&data, start + 4).ok_or(malformed.clone())?;
+        let table = |tag: &[u8; 4]| {
+            (0..num_tables as usize)
+                .map(|i| start + 12 + 16 * i)
+                .find(|&rec| data.get(rec..rec + 4) == Some(tag))
+                .and_then(|rec| read_u32(&data, rec + 8))
+                .map(|offset| offset as usize)
+        };
+        let required = |tag: &[u8; 4]| table(tag).ok_or(FontError::Malformed("missing table"));
+        let (head, maxp, hhea) = (required(b"head")?, required(b"maxp")?, required(b"hhea")?);
+        let (hmtx, loca, glyf) = (required(b"hmtx")?, required(b"loca")?, required(b"glyf")?);
+        let cmap = Self::find_cmap(&data, required(b"cmap")?)
+            .ok_or(FontError::Unsupported("character map encoding"))?;
+        let kern = table(b"kern").and_then(|kern| Self::find_kern(&data, kern));
+
+        let read = |v: Option<u16>| v.ok_or(malformed.clone());
+        let units_per_em = read(read_u16(&data, head + 18))?;
+        if !(16..=16384).contains(&units_per_em) {
+            return Err(FontError::Malformed("invalid units per em"));
+        }
+        Ok(Self {
+            units_per_em,
+            long_loca: read(read_u16(&data, head + 50))? != 0,
+            num_glyphs: read(read_u16(&data, maxp + 4))?,
+            ascender: read(read_u16(&data, hhea + 4))? as i16,
+            descender: read(read_u16(&data, hhea + 6))? as i16,
+            line_gap: read(read_u16(&data, hhea + 8))? as i16,
+            num_h_metrics: read(read_u16(&data, hhea + 34))?,
+            cmap,
+            loca,
+            glyf,
+            hmtx,
+            kern,
+            cache: RefCell::new(HashMap::new()),
+            data,
+        })
+    }
+
+    /// Finds a Unicode subtable with format 4 or 12, preferring full Unicode coverage.
+    fn find_cmap(data: &[u8], cmap: usize) -> Option<usize> {
+        let count = read_u16(data, cmap + 2)? as usize;
+        let mut best = None;
+        for i in 0..count {
+            let rec = cmap + 4 + 8 * i;
+            let (platform, encoding) = (read_u16(data, rec)?, read_u16(data, rec + 2)?);
+            let offset = cmap + read_u32(data, rec + 4)? as usize;
+            let unicode = platform == 0 || (platform == 3 && (encoding == 1 || encoding == 10));
+            match read_u16(data, offset)? {
+                12 if unicode => return Some(offset),
+                4 if unicode => best = Some(offset),
+                _ => {}
+            }
+        }
+        best
+    }
+
+    /// Finds the first horizontal format 0 subtable of a `kern` table.
+    fn find_kern(data: &[u8], kern: usize) -> Option<(usize, usize)> {
+        let count = read_u16(data, kern + 2)?;
+        let mut sub = kern + 4;
+        for _ in 0..count {
+            let length = read_u16(data, sub + 2)? as usize;
+            let coverage = read_u16(data, sub + 4)?;
+            if coverage >> 8 == 0 && coverage & 1 != 0 {
+                return Some((sub + 14, read_u16(data, sub + 6)? as usize));
+            }
+            sub += length;
+        }
+        None
+    }
+
+    /// Get the glyph index of a character, 0 (the missing glyph) if the font lacks it.
+    pub fn glyph_index(&self, c: char) -> u16 {
+        self.lookup(c as u32).unwrap_or(0)
+    }
+
+    fn lookup(&self, c: u32) -> Option<u16> {
+        let data = &self.data;
+        let cmap = self.cmap;
+        if read_u16(data, cmap)? == 12 {
+            let groups = read_u32(data, cmap + 12)? as usize;
+            let (mut lo, mut hi) = (0, groups);
+            while lo < hi {
+                let mid = (lo + hi) / 2;
+                let group = cmap + 16 + 12 * mid;
+                let (start, end) = (read_u32(data, group)?, read_u32(data, group + 4)?);
+                if c < start {
+                    hi = mid;
+                } else if c > end {
+                    lo = mid + 1;
+                } else {
+                    return Some((read_u32(data, group + 8)? + c - start) as u16);
+                }
+            }
+            return None;
+        }
+
+        let c = u16::try_from(c).ok()?;
+        let seg_count = read_u16(data, cmap + 6)? as usize / 2;
+        let ends = cmap + 14;
+        let starts = ends + 2 * seg_count + 2;
+        let deltas = starts + 2 * seg_count;
+        let range_offsets = deltas + 2 * seg_count;
+        let seg =
+            (0..seg_count).find(|&i| read_u16(data, ends + 2 * i).is_some_and(|end| end >= c))?;
+        let start = read_u16(data, starts + 2 * seg)?;
+        if c < start {
+            return None;
+        }
+        let delta = read_u16(data, deltas + 2 * seg)?;
+        let range_offset = read_u16(data, range_offsets + 2 * seg)? as usize;
+        if range_offset == 0 {
+            return Some(c.wrapping_add(delta));
+        }
+        let at = range_offsets + 2 * seg + range_offset + 2 * (c - start) as usize;
+        match read_u16(data, at)? {
+            0 => None,
+            g => Some(g.wrapping_add(delta)),
+        }
+    }
+
+    fn advance(&self, glyph: u16) -> u16 {
+        let i = glyph.min(self.num_h_metrics.saturating_sub(1)) as usize;
+        read_u16(&self.data, self.hmtx + 4 * i).unwrap_or(0)
+    }
+
+    /// Kerning between two glyphs in font units.
+    fn kern(&self, left: u16, right: u16) -> i16 {
+        let Some((pairs, count)) = self.kern else {
+            return 0;
+        };
+        let key = (left as u32) << 16 | right as u32;
+        let (mut lo, mut hi) = (0, count);
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            let Some(pair) = read_u32(&self.data, pairs + 6 * mid) else {
+                return 0;
+            };
+            match pair.cmp(&key) {
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+                std::cmp::Ordering::Equal => {
+                    return read_i16(&self.data, pairs + 6 * mid + 4).unwrap_or(0)
+                }
+            }
+        }
+        0
+    }
+
+    fn glyph_range(&self, glyph: u16) -> Option<(usize, usize)> {
+        if glyph >= self.num_glyphs {
+            return None;
+        }
+        let i = glyph as usize;
+        let (start, end) = if self.long_loca {
+            (
+                read_u32(&self.data, self.loca + 4 * i)?,
+                read_u32(&self.data, self.loca + 4 * i + 4)?,
+            )
+        } else {
+            let at = |i| read_u16(&self.data, self.loca + 2 * i).map(|v| v as u32 * 2);
+            (at(i)?, at(i + 1)?)
+        };
+        Some((self.glyf + start as usize, self.glyf + end as usize))
+    }
+
+    /// Appends the outline of a glyph, transformed by `[a, b, c, d, e, f]` (`x' = a * x + c * y + e`, `y' = b * x + d * y + f`).
+    fn outline(&self, glyph: u16, transform: [f32; 6], path: &mut Path, depth: u8) -> Option<()> {
+        let (start, end) = self.glyph_range(glyph)?;
+        if start == end || depth > 8 {
+            return Some(());
+        }
+        let data = &self.data;
+        let contours = read_i16(data, start)?;
+        let [a, b, c, d, e, f] = transform;
+        let apply = |x: f32, y: f32| (a * x + c * y + e, b * x + d * y + f);
+
+        if contours < 0 {
+            // Composite glyph.
+            let mut at = start + 10;
+            loop {
+                let flags = read_u16(data, at)?;
+                let component = read_u16(data, at + 2)?;
+                at += 4;
+                let (dx, dy) = if flags & 1 != 0 {
+                    at += 4;
+                    (
+                        read_i16(data, at - 4)? as f32,
+                        read_i16(data, at - 2)? as f32,
+                    )
+                } else {
+                    at += 2;
+                    let [dx, dy] = data.get(at - 2..at)? else {
+                        return None;
+                    };
+                    (*dx as i8 as f32, *dy as i8 as f32)
+                };
+                // Components positioned by matching points aren't supported, and stay in place.
+                let (dx, dy) = if flags & 2 != 0 { (dx, dy) } else { (0., 0.) };
+                let f2dot14 = |at: usize| read_i16(data, at).map(|v| v as f32 / 16384.);
+                let [ma, mb, mc, md] = if flags & 0x8 != 0 {
+                    at += 2;
+                    let s = f2dot14(at - 2)?;
+                    [s, 0., 0., s]
+                } else if flags & 0x40 != 0 {
+                    at += 4;
+                    [f2dot14(at - 4)?, 0., 0., f2dot14(at - 2)?]
+                } else if flags & 0x80 != 0 {
+                    at += 8;
+                    [
+                        f2dot14(at - 8)?,
+                        f2dot14(at - 6)?,
+                        f2dot14(at - 4)?,
+                        f2dot14(at - 2)?,
+                    ]
+                } else {
+                    [1., 0., 0., 1.]
+                };
+                let inner = [
+                    a * ma + c * mb,
+                    b * ma + d * mb,
+                    a * mc + c * md,
+                    b * mc + d * md,
+                    a * dx + c * dy + e,
+                    b * dx + d * dy + f,
+                ];
+                self.outline(component, inner, path, depth + 1)?;
+                if flags & 0x20 == 0 {
+                    return Some(());
+                }
+            }
+        }
+
+        let contours = contours as usize;
+        let ends_at = start + 10;
+        let point_count = read_u16(data, ends_at + 2 * contours.checked_sub(1)?)? as usize + 1;
+        let instructions = read_u16(data, ends_at + 2 * contours)? as usize;
+        let mut at = ends_at + 2 * contours + 2 + instructions;
+
+        let mut flags = Vec::with_capacity(point_count);
+        while flags.len() < point_count {
+            let flag = *data.get(at)?;
+            at += 1;
+            let repeat = if flag & 0x8 != 0 {
+                at += 1;
+                *data.get(at - 1)? as usize
+            } else {
+                0
+            };
+            for _ in 0..=repeat {
+                flags.push(flag);
+            }
+        }
+        flags.truncate(point_count);
+
+        // Coordinates are deltas, either a byte with a sign flag, or a word unless repeated.
+        let mut coords = |short: u8, same: u8| -> Option<Vec<i32>> {
+            let mut v = 0;
+            flags
+                .iter()
+                .map(|&flag| {
+                    if flag & short != 0 {
+                        let delta = *data.get(at)? as i32;
+                        at += 1;
+                        v += if flag & same != 0 { delta } else { -delta };
+                    } else if flag & same == 0 {
+                        v += read_i16(data, at)? as i32;
+                        at += 2;
+                    }
+                    Some(v)
+                })
+                .collect()
+        };
+        let xs = coords(0x2, 0x10)?;
+        let ys = coords(0x4, 0x20)?;
+
+        let mut first = 0;
+        for i in 0..contours {
+            let last = read_u16(data, ends_at + 2 * i)? as usize;
+            if last < first || last >= point_count {
+                return None;
+            }
+            let points: Vec<_> = (first..=last)
+                .map(|j| (apply(xs[j] as f32, ys[j] as f32), flags[j] & 1 != 0))
+                .collect();
+            first = last + 1;
+            let mid = |p: (f32, f32), q: (f32, f32)| ((p.0 + q.0) / 2., (p.1 + q.1) / 2.);
+
+            // Start on a point on the curve, implied between two control points if there's none.
+            let n = points.len();
+            let start = points.iter().position(|p| p.1);
+            let (origin, skip) = match start {
+                Some(i) => (points[i].0, i),
+                None => (mid(points[0].0, points[1 % n].0), 0),
+            };
+            path.move_to(origin.0, origin.1);
+            let mut control: Option<(f32, f32)> = None;
+            for k in 1..=n {
+                let (p, on) = points[(skip + k) % n];
+                if start.is_none() && k == n {
+                    // Close the loop back through the first control point.
+                    let q = control.unwrap_or(p);
+                    path.quad_to(q.0, q.1, origin.0, origin.1);
+                    control = None;
+                    break;
+                }
+                match (on, control) {
+                    (true, None) => {
+                        path.line_to(p.0, p.1);
+                    }
+                    (true, Some(q)) => {
+                        path.quad_to(q.0, q.1, p.0, p.1);
+                        control = None;
+                    }
+                    (false, None) => control = Some(p),
+                    (false, Some(q)) => {
+                        let m = mid(q, p);
+                        path.quad_to(q.0, q.1, m.0, m.1);
+                        control = Some(p);
+                    }
+                }
+            }
+            if let Some(q) = control {
+                path.quad_to(q.0, q.1, origin.0, origin.1);
+            }
+            path.close();
+        }
+        Some(())
+    }
+
+    /// Rasterizes a glyph at `size` pixels per em, or get it from the cache.
+    fn render(&self, glyph: u16, size: f32) -> Rc<Glyph> {
+        let key = (size.to_bits(), glyph);
+        if let Some(cached) = self.cache.borrow().get(&key) {
+            return cached.clone();
+        }
+        let scale = size / self.units_per_em as f32;
+        let mut path = Path::new();
+        // Font units have y pointing up.
+        self.outline(glyph, [scale, 0., 0., -scale, 0., 0.], &mut path, 0);
+
+        let contours: Vec<_> = path
+            .flatten()
+            .into_iter()
+            .map(|(points, _)| points)
+            .collect();
+        let (mut min, mut max) = ((f32::MAX, f32::MAX), (f32::MIN, f32::MIN));
+        for &(x, y) in contours.iter().flatten() {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+        }
+        let advance = (self.advance(glyph) as f32 * scale).round() as i32;
+        let empty = Glyph {
+            size: Size::default(),
+            offset: Offset::default(),
+            advance,
+            coverage: Vec::new(),
+        };
+        let rendered = match glyph_bounds(min, max, size) {
+            _ if contours.is_empty() => empty,
+            Some((origin, size)) => {
+                let shifted: Vec<Vec<_>> = contours
+                    .iter()
+                    .map(|c| {
+                        c.iter()
+                            .map(|&(x, y)| (x - origin.x as f32, y - origin.y as f32))
+                            .collect()
+                    })
+                    .collect();
+                let mut coverage = vec![0; (size.w * size.h) as usize];
+                rasterize(&shifted, FillRule::NonZero, true, size, |x, y, c| {
+                    coverage[(x as u32 + y as u32 * size.w) as usize] = c;
+                });
+                Glyph {
+                    size,
+                    offset: origin,
+                    advance,
+                    coverage,
+                }
+            }
+            None if glyph != 0 => {
+                let missing = self.render(0, size);
+                self.cache.borrow_mut().insert(key, missing.clone());
+                return missing;
+            }
+            None => empty,
+        };
+        let rendered = Rc::new(rendered);
+        self.cache.borrow_mut().insert(key, rendered.clone());
+        rendered
+    }
+
+    /// Use the font at `size` pixels per em.
+    pub fn scaled(&self, size: f32) -> ScaledFont<'_> {
+        ScaledFont { font: self, size }
+    }
+
+    /// Drop all cached glyphs.
+    pub fn clear_cache(&self) {
+        self.cache.borrow_mut().clear();
+    }
+}
+
+/// Pixel origin and size of an outline spanning `min` to `max`, or `None` if it's implausibly large at `size` pixels per em.
+fn glyph_bounds(min: (f32, f32), max: (f32, f32), size: f32) -> Option<(Offset, Size)> {
+    let limit = MAX_GLYPH_EMS * size.max(1.);
+    // Also rejects NaN.
+    if !(max.0 - min.0 <= limit && max.1 - min.1 <= limit) {
+        return None;
+    }
+    let origin = Offset::new(min.0.floor() as i32, min.1.floor() as i32);
+    let w = u32::try_from(max.0.ceil() as i64 - origin.x as i64).ok()?;
+    let h = u32::try_from(max.1.ceil() as i64 - origin.y as i64).ok()?;
+    w.checked_mul(h)?;
+    Some((origin, Size::new(w, h)))
+}
+
+/// A [`TrueTypeFont`] at a given size, usable for drawing text.
+#[derive(Clone, Copy)]
+pub struct ScaledFont<'a> {
+    font: &'a TrueTypeFont,
+    size: f32,
+}
+
+impl ScaledFont<'_> {
+    fn scale(&self, v: i16) -> i32 {
+        (v as f32 * self.size / self.font.units_per_em as f32).round() as i32
+    }
+}
+
+impl Font for ScaledFont<'_> {
+    fn ascent(&self) -> i32 {
+        self.scale(self.font.ascender)
+    }
+    fn descent(&self) -> i32 {
+        -self.scale(self.font.descender)
+    }
+    fn line_gap(&self) -> i32 {
+        self.scale(self.font.line_gap)
+    }
+    fn has_glyph(&self, c: char) -> bool {
+        self.font.glyph_index(c) != 0
+    }
+    fn glyph(&self, c: char) -> Rc<Glyph> {
+        self.font.render(self.font.glyph_index(c), self.size)
+    }
+    fn kerning(&self, left: char, right: char) -> i32 {
+        let (left, right) = (self.font.glyph_index(left), self.font.glyph_index(right));
+        self.scale(self.font.kern(left, right))
+    }
+}
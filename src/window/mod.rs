@@ -1,3 +1,12 @@
+use std::{
+    error::Error,
+    fmt,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::image::ImageFormat;
+
 #[cfg(all(
     unix,
     feature = "wayland",
@@ -23,8 +32,125 @@ mod wayland;
 ))]
 mod x11;
 
+/// Environment variable selecting the backend used by [`Buffer::draw`](crate::draw::Buffer::draw).
+pub const BACKEND_VAR: &str = "LITE_GRAPHICS_BACKEND";
+
+/// Where a buffer is shown when drawn.
+#[derive(Clone)]
+pub enum Backend {
+    /// A window on X11 or Wayland, picked from `WAYLAND_DISPLAY`.
+    #[cfg(feature = "window")]
+    Window,
+    /// An image file.
+    File(PathBuf, ImageFormat),
+    /// Image data written to standard output.
+    Stdout(ImageFormat),
+}
+
+#[derive(Clone, Debug)]
+pub enum BackendError {
+    /// The value isn't `window`, `file:PATH`, `stdout` or `stdout:EXTENSION`.
+    Invalid(String),
+    /// The extension doesn't match a supported image format.
+    UnknownFormat(String),
+    /// The backend needs a crate feature that isn't enabled.
+    MissingFeature(&'static str),
+}
+
+impl fmt::Display for BackendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid(value) => write!(f, "invalid backend {value:?}"),
+            Self::UnknownFormat(ext) => write!(f, "unknown image format {ext:?}"),
+            Self::MissingFeature(feature) => {
+                write!(f, "backend requires the `{feature}` feature")
+            }
+        }
+    }
+}
+
+impl Error for BackendError {}
+
+/// Get the image format of an extension, naming the feature it needs if disabled.
+fn format(ext: &str) -> Result<ImageFormat, BackendError> {
+    #[cfg(not(feature = "image-png"))]
+    if ext.eq_ignore_ascii_case("png") {
+        return Err(BackendError::MissingFeature("image-png"));
+    }
+    ImageFormat::from_extension(ext).ok_or_else(|| BackendError::UnknownFormat(ext.into()))
+}
+
+impl Backend {
+    /// Parses a backend: `window`, `file:PATH`, `stdout`, or `stdout:EXTENSION`.
+    ///
+    /// Files use the format matching their extension, and plain `stdout` writes a binary PPM.
+    pub fn parse(s: &str) -> Result<Self, BackendError> {
+        match s.split_once(':') {
+            Some(("file", path)) if !path.is_empty() => {
+                let ext = Path::new(path).extension().unwrap_or_default();
+                Ok(Self::File(path.into(), format(&ext.to_string_lossy())?))
+            }
+            Some(("stdout", ext)) => format(ext).map(Self::Stdout),
+            None if s == "stdout" => Ok(Self::Stdout(ImageFormat::Netpbm(Default::default()))),
+            #[cfg(feature = "window")]
+            None if s == "window" => Ok(Self::Window),
+            #[cfg(not(feature = "window"))]
+            None if s == "window" => Err(BackendError::MissingFeature("window")),
+            _ => Err(BackendError::Invalid(s.into())),
+        }
+    }
+
+    /// Get the backend from [`BACKEND_VAR`], or `None` if it isn't set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the variable holds an invalid backend.
+    pub fn from_env() -> Option<Self> {
+        let value = std::env::var(BACKEND_VAR).ok().filter(|v| !v.is_empty())?;
+        match Self::parse(&value) {
+            Ok(backend) => Some(backend),
+            Err(err) => panic!("invalid {BACKEND_VAR}: {err}"),
+        }
+    }
+}
+
 impl crate::draw::Buffer {
+    /// Shows the buffer with the backend from [`BACKEND_VAR`], or in a window if it isn't set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the backend fails, or if no backend is set without the `window` feature.
     pub fn draw(&self) {
+        let backend = Backend::from_env();
+        #[cfg(feature = "window")]
+        let backend = backend.or(Some(Backend::Window));
+        let Some(backend) = backend else {
+            panic!("no window support, set {BACKEND_VAR} to draw to a file or stdout");
+        };
+        if let Err(err) = self.draw_with(&backend) {
+            panic!("failed to draw: {err}");
+        }
+    }
+
+    /// Shows the buffer with the given backend.
+    pub fn draw_with(&self, backend: &Backend) -> io::Result<()> {
+        match backend {
+            #[cfg(feature = "window")]
+            Backend::Window => {
+                self.window();
+                Ok(())
+            }
+            Backend::File(path, format) => std::fs::write(path, self.encode(*format)),
+            Backend::Stdout(format) => {
+                let mut stdout = io::stdout().lock();
+                stdout.write_all(&self.encode(*format))?;
+                stdout.flush()
+            }
+        }
+    }
+
+    #[cfg(feature = "window")]
+    fn window(&self) {
         #[cfg(all(
             unix,
             not(any(
@@ -0,0 +1,137 @@
+#!/usr/bin/env python3
+"""Writes the QOI fixtures used by tests/qoi.rs, with their pixels as raw RGBA.
+
+The encoder follows the QOI specification (https://qoiformat.org/qoi-specification.pdf),
+choosing chunks in the same order as the reference qoi.h, and shares no code with the crate.
+Run from tests/data: python3 qoi_fixtures.py
+"""
+
+import random
+import struct
+
+
+def index_position(p):
+    r, g, b, a = p
+    return (r * 3 + g * 5 + b * 7 + a * 11) % 64
+
+
+def wrap(v):
+    return (v + 128) % 256 - 128
+
+
+def encode(pixels, width, height, channels, colorspace):
+    out = bytearray(b"qoif" + struct.pack(">IIBB", width, height, channels, colorspace))
+    index = [(0, 0, 0, 0)] * 64
+    prev = (0, 0, 0, 255)
+    run = 0
+    for i, p in enumerate(pixels):
+        if p == prev:
+            run += 1
+            if run == 62 or i == len(pixels) - 1:
+                out.append(0xC0 | (run - 1))
+                run = 0
+            continue
+        if run:
+            out.append(0xC0 | (run - 1))
+            run = 0
+
+        k = index_position(p)
+        if index[k] == p:
+            out.append(k)  # QOI_OP_INDEX
+        else:
+            index[k] = p
+            if p[3] == prev[3]:
+                dr, dg, db = (wrap(p[c] - prev[c]) for c in range(3))
+                dr_dg, db_dg = dr - dg, db - dg
+                if -3 < dr < 2 and -3 < dg < 2 and -3 < db < 2:
+                    out.append(0x40 | (dr + 2) << 4 | (dg + 2) << 2 | (db + 2))  # QOI_OP_DIFF
+                elif -33 < dg < 32 and -9 < dr_dg < 8 and -9 < db_dg < 8:
+                    out += bytes([0x80 | (dg + 32), (dr_dg + 8) << 4 | (db_dg + 8)])  # QOI_OP_LUMA
+                else:
+                    out += bytes([0xFE, *p[:3]])  # QOI_OP_RGB
+            else:
+                out += bytes([0xFF, *p])  # QOI_OP_RGBA
+        prev = p
+    return bytes(out + b"\0" * 7 + b"\1")
+
+
+def decode(data):
+    width, height, _, _ = struct.unpack(">IIBB", data[4:14])
+    index = [(0, 0, 0, 0)] * 64
+    p = (0, 0, 0, 255)
+    pos = 14
+    out = []
+    while len(out) < width * height:
+        b = data[pos]
+        pos += 1
+        if b == 0xFE:
+            p = (*data[pos : pos + 3], p[3])
+            pos += 3
+        elif b == 0xFF:
+            p = tuple(data[pos : pos + 4])
+            pos += 4
+        elif b >> 6 == 0:
+            p = index[b]
+        elif b >> 6 == 1:
+            diff = ((b >> 4 & 3) - 2, (b >> 2 & 3) - 2, (b & 3) - 2)
+            p = (*((p[c] + diff[c]) % 256 for c in range(3)), p[3])
+        elif b >> 6 == 2:
+            dg = (b & 63) - 32
+            n = data[pos]
+            pos += 1
+            diff = (dg + (n >> 4) - 8, dg, dg + (n & 15) - 8)
+            p = (*((p[c] + diff[c]) % 256 for c in range(3)), p[3])
+        else:
+            out += [p] * (b & 63)
+        index[index_position(p)] = p
+        out.append(p)
+    return out
+
+
+def images():
+    random.seed(7)
+
+    # Horizontal gradients with flat stripes, long enough for several runs.
+    w, h = 64, 48
+    pixels = [
+        ((x * 4) & 255, (y * 5) & 255, 128 if x < 32 else 40, 255) if y % 12 else (200, 30, 30, 255)
+        for y in range(h)
+        for x in range(w)
+    ]
+    yield "gradient_srgb", w, h, 3, 0, pixels
+
+    # A random walk with steps of varying size, hitting every RGB chunk type.
+    w, h = 40, 30
+    pixels = []
+    v = [100, 100, 100]
+    for _ in range(w * h):
+        step = random.choice([1, 1, 3, 20, 90])
+        v = [(c + random.randint(-step, step)) % 256 for c in v]
+        if random.random() < 0.1:
+            v = [10, 200, 30]
+        pixels.append((v[0], v[1], v[2], 255))
+    yield "noise_linear", w, h, 3, 1, pixels
+
+    # An opaque disk on a fully transparent background.
+    w, h = 48, 32
+    pixels = [
+        (255, x * 5, y * 7, 255) if (x - 24) ** 2 + (y - 16) ** 2 < 150 else (0, 0, 0, 0)
+        for y in range(h)
+        for x in range(w)
+    ]
+    yield "cutout", w, h, 4, 0, pixels
+
+    # Every pixel with a different alpha from its neighbors.
+    w, h = 32, 32
+    pixels = [(x * 8, 255 - y * 8, 120, (x * y) % 256) for y in range(h) for x in range(w)]
+    yield "alpha", w, h, 4, 0, pixels
+
+
+if __name__ == "__main__":
+    for name, w, h, channels, colorspace, pixels in images():
+        data = encode(pixels, w, h, channels, colorspace)
+        assert decode(data) == pixels
+        with open(name + ".qoi", "wb") as f:
+            f.write(data)
+        with open(name + ".rgba", "wb") as f:
+            f.write(bytes(c for p in pixels for c in p))
@@ -0,0 +1,28 @@
+#![cfg(feature = "image-png")]
+
+use lite_graphics::{color::Color, Buffer, Drawable, Offset};
+
+fn image() -> Buffer {
+    let buf = Buffer::new(90, 60);
+    buf.fill_circle_aa(Offset::new(45, 30), 25, Color::RED);
+    buf.text(Offset::new(10, 10), "PNG", Color::BLUE);
+    buf
+}
+
+#[test]
+fn round_trip() {
+    let buf = image();
+    let decoded = Buffer::from_png(&buf.to_png_bytes()).unwrap();
+    assert_eq!(*decoded.data(), *buf.data());
+}
+
+#[test]
+fn malformed() {
+    let data = image().to_png_bytes();
+    assert!(Buffer::from_png(&data[..data.len() / 2]).is_err());
+
+    // The IHDR chunk's checksum.
+    let mut bad = data;
+    bad[29] ^= 1;
+    assert!(Buffer::from_png(&bad).is_err());
+}
@@ -0,0 +1,152 @@
+//! QOI reference images in `tests/data` are written by `qoi_fixtures.py`, an implementation of the
+//! specification independent from this crate, alongside their pixels as raw RGBA in `.rgba` files.
+
+use lite_graphics::{
+    color::{Color, Rgba},
+    image::{QoiColorspace, QoiInfo},
+    Buffer, Drawable, Offset, Overlay, Rect,
+};
+
+const GRADIENT: &[u8] = include_bytes!("data/gradient_srgb.qoi");
+const GRADIENT_RGBA: &[u8] = include_bytes!("data/gradient_srgb.rgba");
+const NOISE: &[u8] = include_bytes!("data/noise_linear.qoi");
+const NOISE_RGBA: &[u8] = include_bytes!("data/noise_linear.rgba");
+const CUTOUT: &[u8] = include_bytes!("data/cutout.qoi");
+const CUTOUT_RGBA: &[u8] = include_bytes!("data/cutout.rgba");
+const ALPHA: &[u8] = include_bytes!("data/alpha.qoi");
+const ALPHA_RGBA: &[u8] = include_bytes!("data/alpha.rgba");
+
+fn rgb(rgba: &[u8]) -> Vec<u8> {
+    rgba.chunks_exact(4)
+        .flat_map(|p| [p[0], p[1], p[2]])
+        .collect()
+}
+
+/// Blends straight RGBA pixels over a background.
+fn over(rgba: &[u8], background: Rgba) -> Vec<u8> {
+    let mix = |c: u8, bg: u8, a: u8| {
+        ((c as u32 * a as u32 + bg as u32 * (255 - a as u32) + 127) / 255) as u8
+    };
+    rgba.chunks_exact(4)
+        .flat_map(|p| {
+            [
+                mix(p[0], background.r, p[3]),
+                mix(p[1], background.g, p[3]),
+                mix(p[2], background.b, p[3]),
+            ]
+        })
+        .collect()
+}
+
+fn max_diff(a: &[u8], b: &[u8]) -> u8 {
+    assert_eq!(a.len(), b.len());
+    a.iter()
+        .zip(b)
+        .map(|(a, b)| a.abs_diff(*b))
+        .max()
+        .unwrap_or(0)
+}
+
+#[test]
+fn rgb_reference_images() {
+    for (data, rgba, colorspace) in [
+        (GRADIENT, GRADIENT_RGBA, QoiColorspace::Srgb),
+        (NOISE, NOISE_RGBA, QoiColorspace::Linear),
+    ] {
+        let (buf, info) = Buffer::decode_qoi(data).unwrap();
+        assert_eq!(
+            info,
+            QoiInfo {
+                channels: 3,
+                colorspace
+            }
+        );
+        assert_eq!(*buf.data(), rgb(rgba));
+        assert_eq!(buf.encode_qoi(info.colorspace), data);
+    }
+}
+
+#[test]
+fn rgba_reference_images() {
+    // Fully opaque and fully transparent pixels survive premultiplication unchanged.
+    let (overlay, info) =
+        Overlay::decode_qoi(Buffer::new(1, 1), Offset::new(0, 0), CUTOUT).unwrap();
+    assert_eq!(
+        info,
+        QoiInfo {
+            channels: 4,
+            colorspace: QoiColorspace::Srgb
+        }
+    );
+    assert_eq!(overlay.encode_qoi(info.colorspace), CUTOUT);
+    for background in [Rgba::BLACK, Rgba::WHITE] {
+        let (buf, _) = Buffer::decode_qoi_with_background(CUTOUT, background).unwrap();
+        assert_eq!(*buf.data(), over(CUTOUT_RGBA, background));
+    }
+
+    // Other alpha values are kept, within the rounding of premultiplication.
+    let (overlay, info) = Overlay::decode_qoi(Buffer::new(1, 1), Offset::new(0, 0), ALPHA).unwrap();
+    assert_eq!(info.channels, 4);
+    let encoded = overlay.encode_qoi(info.colorspace);
+    for background in [Rgba::BLACK, Rgba::WHITE] {
+        let (buf, _) = Buffer::decode_qoi_with_background(&encoded, background).unwrap();
+        assert!(max_diff(&buf.data(), &over(ALPHA_RGBA, background)) <= 1);
+        let (buf, _) = Buffer::decode_qoi_with_background(ALPHA, background).unwrap();
+        assert_eq!(*buf.data(), over(ALPHA_RGBA, background));
+    }
+}
+
+#[test]
+fn buffer_round_trip() {
+    let buf = Buffer::new(120, 80);
+    buf.fill_circle_aa(Offset::new(60, 40), 30, Color::RED);
+    buf.text(Offset::new(10, 10), "QOI", Color::BLUE);
+    let data = buf.encode_qoi(QoiColorspace::Linear);
+    let (decoded, info) = Buffer::decode_qoi(&data).unwrap();
+    assert_eq!(
+        info,
+        QoiInfo {
+            channels: 3,
+            colorspace: QoiColorspace::Linear
+        }
+    );
+    assert_eq!(*decoded.data(), *buf.data());
+}
+
+#[test]
+fn overlay_round_trip() {
+    let overlay = Overlay::new(
+        Buffer::new(100, 100),
+        Rect {
+            x: 10,
+            y: 10,
+            w: 60,
+            h: 40,
+        },
+    );
+    overlay.fill_circle_aa(Offset::new(30, 20), 15, Color::Rgba(Rgba::GREEN.set_a(160)));
+    let data = overlay.encode_qoi(QoiColorspace::Srgb);
+    let (decoded, info) =
+        Overlay::decode_qoi(Buffer::new(100, 100), Offset::new(10, 10), &data).unwrap();
+    assert_eq!(
+        info,
+        QoiInfo {
+            channels: 4,
+            colorspace: QoiColorspace::Srgb
+        }
+    );
+    assert_eq!(decoded.encode_qoi(QoiColorspace::Srgb), data);
+    assert_eq!(*decoded.write().data(), *overlay.write().data());
+}
+
+#[test]
+fn malformed() {
+    assert!(Buffer::decode_qoi(b"qoif").is_err());
+    assert!(Buffer::decode_qoi(&GRADIENT[..GRADIENT.len() / 2]).is_err());
+    let mut bad = GRADIENT.to_vec();
+    bad[12] = 5;
+    assert!(Buffer::decode_qoi(&bad).is_err());
+    bad[12] = 3;
+    bad[13] = 2;
+    assert!(Buffer::decode_qoi(&bad).is_err());
+}